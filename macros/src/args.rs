@@ -0,0 +1,178 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Ident, Lit, LitInt, LitStr, Meta, Type};
+
+/// A single command option, parsed from a struct field (or a function parameter).
+pub struct ArgField {
+    pub ident: Ident,
+    pub ty: Type,
+    pub name: String,
+    pub description: String,
+    pub min: Option<Lit>,
+    pub max: Option<Lit>,
+    pub min_length: Option<LitInt>,
+    pub max_length: Option<LitInt>,
+    pub autocomplete: bool,
+}
+
+impl ArgField {
+    /// Parses the `#[arg(...)]` and doc attributes of a field.
+    pub fn parse(ident: Ident, ty: Type, attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut field = ArgField {
+            name: ident.to_string().trim_start_matches("r#").to_string(),
+            ident,
+            ty,
+            description: String::new(),
+            min: None,
+            max: None,
+            min_length: None,
+            max_length: None,
+            autocomplete: false,
+        };
+
+        for attr in attrs {
+            if attr.path().is_ident("arg") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("description") {
+                        field.description = meta.value()?.parse::<LitStr>()?.value();
+                    } else if meta.path.is_ident("rename") {
+                        field.name = meta.value()?.parse::<LitStr>()?.value();
+                    } else if meta.path.is_ident("min") {
+                        field.min = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("max") {
+                        field.max = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("min_length") {
+                        field.min_length = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("max_length") {
+                        field.max_length = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("autocomplete") {
+                        field.autocomplete = true;
+                    } else {
+                        return Err(meta.error("unknown `arg` attribute"));
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+
+        if field.description.is_empty() {
+            field.description = doc_comment(attrs);
+        }
+        if field.description.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &field.ident,
+                "command options need a description: add `#[arg(description = \"...\")]` or a doc comment",
+            ));
+        }
+
+        Ok(field)
+    }
+
+    /// Builds the `CreateCommandOption` for this field.
+    pub fn option(&self) -> syn::Result<TokenStream> {
+        let ty = &self.ty;
+        let name = &self.name;
+        let description = &self.description;
+        let mut option = quote! {
+            crate::args::option::<#ty>(#name, #description)
+        };
+
+        for (lit, int_method, number_method) in [
+            (&self.min, quote!(min_int_value), quote!(min_number_value)),
+            (&self.max, quote!(max_int_value), quote!(max_number_value)),
+        ] {
+            match lit {
+                Some(Lit::Int(value)) => option.extend(quote!(.#int_method(#value))),
+                Some(Lit::Float(value)) => option.extend(quote!(.#number_method(#value))),
+                Some(other) => {
+                    return Err(syn::Error::new_spanned(other, "expected an integer or float literal"));
+                }
+                None => {}
+            }
+        }
+        if let Some(value) = &self.min_length {
+            option.extend(quote!(.min_length(#value)));
+        }
+        if let Some(value) = &self.max_length {
+            option.extend(quote!(.max_length(#value)));
+        }
+        if self.autocomplete {
+            option.extend(quote!(.set_autocomplete(true)));
+        }
+
+        Ok(option)
+    }
+
+    /// Builds the expression extracting this field from `options`.
+    pub fn extract(&self) -> TokenStream {
+        let name = &self.name;
+        quote! {
+            crate::args::extract(options, #name)?
+        }
+    }
+}
+
+/// Joins the `///` lines of an item into a single line of text.
+pub fn doc_comment(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(s), .. }) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(named) => named
+                .named
+                .iter()
+                .map(|f| ArgField::parse(f.ident.clone().unwrap(), f.ty.clone(), &f.attrs))
+                .collect::<syn::Result<Vec<_>>>()?,
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(ident, "`CommandArgs` needs named fields"));
+            }
+        },
+        _ => return Err(syn::Error::new_spanned(ident, "`CommandArgs` can only be derived for structs")),
+    };
+
+    let unit = matches!(&input.data, Data::Struct(data) if matches!(data.fields, Fields::Unit));
+    impl_command_args(ident, &fields, unit)
+}
+
+/// Implements `CommandArgs` for the struct `ident` made of `fields` (a unit struct if `unit`).
+pub fn impl_command_args(ident: &Ident, fields: &[ArgField], unit: bool) -> syn::Result<TokenStream> {
+    let options = fields.iter().map(ArgField::option).collect::<syn::Result<Vec<_>>>()?;
+    let idents = fields.iter().map(|f| &f.ident);
+    let extracts = fields.iter().map(ArgField::extract);
+    let construct = if unit {
+        quote!(Self)
+    } else {
+        quote!(Self { #(#idents: #extracts,)* })
+    };
+
+    Ok(quote! {
+        impl crate::args::CommandArgs for #ident {
+            fn options() -> ::std::vec::Vec<::serenity::all::CreateCommandOption> {
+                ::std::vec![#(#options),*]
+            }
+
+            #[allow(unused_variables)]
+            fn parse(
+                options: &[::serenity::all::ResolvedOption<'_>],
+            ) -> ::std::result::Result<Self, crate::args::ArgError> {
+                ::std::result::Result::Ok(#construct)
+            }
+        }
+    })
+}
@@ -0,0 +1,202 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::meta::ParseNestedMeta;
+use syn::{Expr, FnArg, Ident, ItemFn, LitStr, Pat};
+
+use crate::args::{doc_comment, impl_command_args, ArgField};
+
+/// The arguments of `#[slash_command(...)]`.
+#[derive(Default)]
+pub struct CommandAttrs {
+    name: Option<LitStr>,
+    description: Option<LitStr>,
+    category: Option<Expr>,
+    scope: Option<Expr>,
+    checks: Option<Expr>,
+    cooldown: Option<Expr>,
+    defer: Option<Expr>,
+    prefix: Option<Expr>,
+}
+
+impl CommandAttrs {
+    pub fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("description") {
+            self.description = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("category") {
+            self.category = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("scope") {
+            self.scope = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("checks") {
+            self.checks = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("cooldown") {
+            self.cooldown = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("defer") {
+            self.defer = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("prefix") {
+            self.prefix = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("unknown `slash_command` attribute"));
+        }
+        Ok(())
+    }
+}
+
+pub fn expand(attrs: CommandAttrs, mut function: ItemFn) -> syn::Result<TokenStream> {
+    let fn_ident = function.sig.ident.clone();
+    if function.sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(&function.sig, "`#[slash_command]` functions must be `async`"));
+    }
+    if function.sig.inputs.len() < 3 {
+        return Err(syn::Error::new_spanned(
+            &function.sig,
+            "`#[slash_command]` functions take `ctx: &Context`, `invocation: &Invocation<'_>` \
+             and `response: &Responder<'_>` first",
+        ));
+    }
+
+    // Every parameter after `ctx`, `invocation` and `response` becomes an option.
+    let mut fields = Vec::new();
+    for input in function.sig.inputs.iter_mut().skip(3) {
+        let FnArg::Typed(param) = input else {
+            return Err(syn::Error::new_spanned(input, "`#[slash_command]` functions can't take `self`"));
+        };
+        let Pat::Ident(pat) = &*param.pat else {
+            return Err(syn::Error::new_spanned(&param.pat, "command options must be plain identifiers"));
+        };
+        fields.push(ArgField::parse(pat.ident.clone(), (*param.ty).clone(), &param.attrs)?);
+        param.attrs.retain(|attr| !attr.path().is_ident("arg"));
+    }
+
+    let name = attrs
+        .name
+        .unwrap_or_else(|| LitStr::new(&fn_ident.to_string(), fn_ident.span()));
+    let description = match attrs.description {
+        Some(description) => description,
+        None => {
+            let doc = doc_comment(&function.attrs);
+            if doc.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &fn_ident,
+                    "slash commands need a description: add `description = \"...\"` or a doc comment",
+                ));
+            }
+            LitStr::new(&doc, Span::call_site())
+        }
+    };
+
+    let vis = &function.vis;
+    let command = format_ident!("{}Command", pascal_case(&fn_ident));
+    let (args_type, args_impl, args) = if fields.is_empty() {
+        (quote!(()), quote!(), quote!())
+    } else {
+        let args_ident = format_ident!("{}Args", command);
+        let field_idents: Vec<_> = fields.iter().map(|f| &f.ident).collect();
+        let field_types = fields.iter().map(|f| &f.ty);
+        let args_impl = impl_command_args(&args_ident, &fields, false)?;
+        (
+            quote!(#args_ident),
+            quote! {
+                #[doc(hidden)]
+                #vis struct #args_ident {
+                    #(#field_idents: #field_types,)*
+                }
+
+                #args_impl
+            },
+            quote!(#(args.#field_idents),*),
+        )
+    };
+
+    let category = attrs.category.map(|category| quote! {
+        fn category(&self) -> &'static str {
+            #category
+        }
+    });
+    let scope = attrs.scope.map(|scope| quote! {
+        fn scope(&self) -> crate::scope::CommandScope {
+            #scope
+        }
+    });
+    let checks = attrs.checks.map(|checks| quote! {
+        fn checks(&self) -> ::std::vec::Vec<crate::checks::Check> {
+            ::std::convert::Into::into(#checks)
+        }
+    });
+    let cooldown = attrs.cooldown.map(|cooldown| quote! {
+        fn cooldown(&self) -> ::std::option::Option<crate::cooldown::Cooldown> {
+            ::std::option::Option::Some(#cooldown)
+        }
+    });
+    let defer = attrs.defer.map(|defer| quote! {
+        fn defer(&self) -> crate::response::Defer {
+            #defer
+        }
+    });
+    let prefix = attrs.prefix.map(|prefix| quote! {
+        fn prefix(&self) -> bool {
+            #prefix
+        }
+    });
+
+    Ok(quote! {
+        #function
+
+        #[doc = concat!("The `/", #name, "` command, defined by [`", stringify!(#fn_ident), "`].")]
+        #vis struct #command;
+
+        #args_impl
+
+        impl crate::registration::HasInstance for #command {
+            const INSTANCE: Self = #command;
+        }
+
+        #[::async_trait::async_trait]
+        impl crate::command::SlashCommand for #command {
+            type Args = #args_type;
+
+            fn name(&self) -> &'static str {
+                #name
+            }
+
+            fn description(&self) -> &'static str {
+                #description
+            }
+
+            #category
+            #scope
+            #checks
+            #cooldown
+            #defer
+            #prefix
+
+            #[allow(unused_variables)]
+            async fn run(
+                &self,
+                ctx: &::serenity::all::Context,
+                invocation: &crate::invocation::Invocation<'_>,
+                response: &crate::response::Responder<'_>,
+                args: #args_type,
+            ) -> crate::error::CommandResult {
+                #fn_ident(ctx, invocation, response, #args).await
+            }
+        }
+
+        crate::register!(#command as SlashCommand);
+    })
+}
+
+/// Turns a `snake_case` function name into `PascalCase`.
+fn pascal_case(ident: &Ident) -> String {
+    ident
+        .to_string()
+        .trim_start_matches("r#")
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+        })
+        .collect()
+}
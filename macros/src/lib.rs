@@ -0,0 +1,78 @@
+//! Procedural macros for the bot's command framework.
+//!
+//! These macros only generate code against paths inside the bot crate
+//! (`crate::args`, `crate::command`, ...), so they are meant to be used from
+//! within the bot itself and not as a general purpose library.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ItemFn};
+
+mod args;
+mod command;
+
+/// Derives `CommandArgs` for a struct with named fields.
+///
+/// Every field becomes one slash command option. The option name is the field
+/// name, its Discord type is inferred from the field type and `Option<T>` fields
+/// are registered as optional.
+///
+/// Supported field attributes (`#[arg(...)]`):
+/// * `description = "..."` - the option description (falls back to the doc comment).
+/// * `rename = "..."` - use a different option name than the field name.
+/// * `min = N`, `max = N` - bounds for integer and number options.
+/// * `min_length = N`, `max_length = N` - bounds for string options.
+/// * `autocomplete` - ask Discord for autocomplete suggestions while the user types.
+///
+/// ```ignore
+/// #[derive(CommandArgs)]
+/// pub struct SumArgs {
+///     #[arg(description = "First number")]
+///     a: i64,
+///     /// Second number
+///     b: Option<i64>,
+/// }
+/// ```
+#[proc_macro_derive(CommandArgs, attributes(arg))]
+pub fn derive_command_args(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    args::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Defines a slash command from an `async fn`.
+///
+/// The function takes the context, the invocation and the response handle, then one parameter per option
+/// (with the same `#[arg(...)]` attributes as `CommandArgs` fields), and returns a
+/// `CommandResult`. The macro generates a `<Name>Command` struct implementing
+/// `SlashCommand` and registers it.
+///
+/// Supported arguments (`#[slash_command(...)]`):
+/// * `name = "..."` - the command name (defaults to the function name).
+/// * `description = "..."` - the command description (falls back to the doc comment).
+/// * `category = "..."` - the category of the command in `/help`.
+/// * `scope = expr`, `checks = expr`, `cooldown = expr`, `defer = expr`, `prefix = expr` - see the
+///   `SlashCommand` methods.
+///
+/// ```ignore
+/// #[slash_command(description = "Adds two numbers")]
+/// async fn add(
+///     ctx: &Context,
+///     invocation: &Invocation<'_>,
+///     response: &Responder<'_>,
+///     #[arg(description = "First number")] a: i64,
+///     #[arg(description = "Second number")] b: i64,
+/// ) -> CommandResult {
+///     // ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn slash_command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut attrs = command::CommandAttrs::default();
+    let parser = syn::meta::parser(|meta| attrs.parse(meta));
+    parse_macro_input!(attr with parser);
+    let function = parse_macro_input!(item as ItemFn);
+    command::expand(attrs, function)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
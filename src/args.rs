@@ -0,0 +1,250 @@
+use std::fmt;
+
+use serenity::all::*;
+use crate::i18n::localize_option;
+
+pub use discord_bot_macros::CommandArgs;
+
+/// A set of typed arguments for a slash command.
+///
+/// Usually derived with `#[derive(CommandArgs)]`: every field of the struct becomes
+/// one command option, and the parsed struct is handed to `SlashCommand::run`.
+/// Commands without options use `()`.
+pub trait CommandArgs: Sized + Send {
+    /// The options to register for this set of arguments.
+    fn options() -> Vec<CreateCommandOption>;
+
+    /// Parses the arguments out of the resolved options of an interaction.
+    fn parse(options: &[ResolvedOption<'_>]) -> Result<Self, ArgError>;
+}
+
+impl CommandArgs for () {
+    fn options() -> Vec<CreateCommandOption> {
+        vec![]
+    }
+
+    fn parse(_options: &[ResolvedOption<'_>]) -> Result<Self, ArgError> {
+        Ok(())
+    }
+}
+
+/// A type that can be used as a field of a `CommandArgs` struct.
+///
+/// Implemented for the primitive option types (`String`, `i64`, `f64`, `bool`),
+/// for Discord entities (`User`, `Role`, `PartialChannel`, `Attachment` and their IDs)
+/// and for `Option<T>`, which makes the option optional.
+pub trait ArgValue: Sized + Send {
+    /// The Discord option type this value is registered as.
+    const KIND: CommandOptionType;
+
+    /// Whether the option must be provided by the user.
+    const REQUIRED: bool = true;
+
+    /// Converts a resolved option value, returning `None` if it has the wrong type.
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self>;
+
+    /// The value to use when the option was not provided, if any.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// An error raised while parsing command arguments.
+#[derive(Debug, Clone)]
+pub enum ArgError {
+    /// A required option was not provided.
+    Missing(&'static str),
+    /// An option was provided with a value of the wrong type.
+    WrongType {
+        name: &'static str,
+        expected: CommandOptionType,
+    },
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgError::Missing(name) => write!(f, "Missing required option `{name}`."),
+            ArgError::WrongType { name, expected } => {
+                write!(f, "Option `{name}` must be {}.", kind_name(*expected))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+/// Returns a human readable name for an option type, used in error messages.
+pub fn kind_name(kind: CommandOptionType) -> &'static str {
+    match kind {
+        CommandOptionType::String => "a text",
+        CommandOptionType::Integer => "an integer",
+        CommandOptionType::Number => "a number",
+        CommandOptionType::Boolean => "true or false",
+        CommandOptionType::User => "a user",
+        CommandOptionType::Channel => "a channel",
+        CommandOptionType::Role => "a role",
+        CommandOptionType::Mentionable => "a user or role",
+        CommandOptionType::Attachment => "an attachment",
+        _ => "a valid value",
+    }
+}
+
+/// Builds the `CreateCommandOption` for a field of type `T`.
+///
+/// Used by the `CommandArgs` derive. The option gets the translations of its name and
+/// description, see `i18n::localize_option`.
+pub fn option<T: ArgValue>(name: &str, description: &str) -> CreateCommandOption {
+    localize_option(CreateCommandOption::new(T::KIND, name, description).required(T::REQUIRED), name)
+}
+
+/// Extracts the option called `name` from `options` as a `T`.
+///
+/// Used by the `CommandArgs` derive.
+pub fn extract<T: ArgValue>(options: &[ResolvedOption<'_>], name: &'static str) -> Result<T, ArgError> {
+    match options.iter().find(|option| option.name == name) {
+        Some(option) => T::from_value(&option.value).ok_or(ArgError::WrongType {
+            name,
+            expected: T::KIND,
+        }),
+        None => T::from_missing().ok_or(ArgError::Missing(name)),
+    }
+}
+
+impl<T: ArgValue> ArgValue for Option<T> {
+    const KIND: CommandOptionType = T::KIND;
+    const REQUIRED: bool = false;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        T::from_value(value).map(Some)
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl ArgValue for String {
+    const KIND: CommandOptionType = CommandOptionType::String;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::String(s) => Some(s.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl ArgValue for i64 {
+    const KIND: CommandOptionType = CommandOptionType::Integer;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
+impl ArgValue for f64 {
+    const KIND: CommandOptionType = CommandOptionType::Number;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::Number(n) => Some(*n),
+            ResolvedValue::Integer(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+}
+
+impl ArgValue for bool {
+    const KIND: CommandOptionType = CommandOptionType::Boolean;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl ArgValue for User {
+    const KIND: CommandOptionType = CommandOptionType::User;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::User(user, _) => Some((*user).clone()),
+            _ => None,
+        }
+    }
+}
+
+impl ArgValue for UserId {
+    const KIND: CommandOptionType = CommandOptionType::User;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::User(user, _) => Some(user.id),
+            ResolvedValue::Unresolved(Unresolved::User(id)) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+impl ArgValue for Role {
+    const KIND: CommandOptionType = CommandOptionType::Role;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::Role(role) => Some((*role).clone()),
+            _ => None,
+        }
+    }
+}
+
+impl ArgValue for RoleId {
+    const KIND: CommandOptionType = CommandOptionType::Role;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::Role(role) => Some(role.id),
+            ResolvedValue::Unresolved(Unresolved::RoleId(id)) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+impl ArgValue for PartialChannel {
+    const KIND: CommandOptionType = CommandOptionType::Channel;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::Channel(channel) => Some((*channel).clone()),
+            _ => None,
+        }
+    }
+}
+
+impl ArgValue for ChannelId {
+    const KIND: CommandOptionType = CommandOptionType::Channel;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::Channel(channel) => Some(channel.id),
+            ResolvedValue::Unresolved(Unresolved::Channel(id)) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+impl ArgValue for Attachment {
+    const KIND: CommandOptionType = CommandOptionType::Attachment;
+
+    fn from_value(value: &ResolvedValue<'_>) -> Option<Self> {
+        match value {
+            ResolvedValue::Attachment(attachment) => Some((*attachment).clone()),
+            _ => None,
+        }
+    }
+}
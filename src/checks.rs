@@ -0,0 +1,105 @@
+use std::fmt;
+
+use serenity::all::*;
+use crate::config::CONFIG;
+use crate::error::{CommandError, CommandResult};
+use crate::invocation::Invocation;
+
+/// A precondition that must hold before a command runs.
+///
+/// Checks are declared with `SlashCommand::checks` (and `Subcommand::checks`), evaluated by the
+/// dispatcher before `run`, and also reflected in the registration when Discord supports it,
+/// so users don't even see commands they can't use.
+#[derive(Clone, Copy)]
+pub enum Check {
+    /// The command can only be used in a guild. Registers the command with `dm_permission(false)`.
+    GuildOnly,
+    /// The user needs all of these permissions. Registers them as `default_member_permissions`.
+    Permissions(Permissions),
+    /// The user must be one of the bot owners (`OWNER_IDS`).
+    OwnerOnly,
+    /// The user must have this role.
+    Role(RoleId),
+    /// A custom precondition, returning the reason of the denial when it fails.
+    Custom(fn(&Invocation<'_>) -> Result<(), String>),
+}
+
+impl Check {
+    /// Evaluates the check, returning why it failed.
+    fn evaluate(&self, invocation: &Invocation<'_>) -> Result<(), String> {
+        match self {
+            Check::GuildOnly if invocation.guild_id.is_none() => {
+                Err("it only works in servers".to_string())
+            }
+            Check::Permissions(required) => {
+                let missing = *required - invocation.permissions.unwrap_or_else(Permissions::empty);
+                if invocation.guild_id.is_none() {
+                    Err("it only works in servers".to_string())
+                } else if missing.is_empty() || invocation.permissions.is_some_and(|p| p.administrator()) {
+                    Ok(())
+                } else {
+                    Err(format!("you need the {missing} permission"))
+                }
+            }
+            Check::OwnerOnly if !CONFIG.owners.contains(&invocation.user.id) => {
+                Err("it is reserved to the bot owners".to_string())
+            }
+            Check::Role(role_id) if !invocation.roles.contains(role_id) => {
+                Err(format!("you need the {} role", role_id.mention()))
+            }
+            Check::Custom(check) => check(invocation),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    /// Describes the check for the help, e.g. `Needs the Manage Messages permission`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::GuildOnly => write!(f, "Only in servers"),
+            Check::Permissions(permissions) => write!(f, "Needs the {permissions} permission"),
+            Check::OwnerOnly => write!(f, "Bot owners only"),
+            Check::Role(role_id) => write!(f, "Needs the {} role", role_id.mention()),
+            Check::Custom(_) => write!(f, "Custom requirements"),
+        }
+    }
+}
+
+/// Evaluates `checks` in order, failing with a permission error on the first one that doesn't hold.
+pub fn run_checks(checks: &[Check], invocation: &Invocation<'_>) -> CommandResult {
+    for check in checks {
+        if let Err(reason) = check.evaluate(invocation) {
+            return Err(CommandError::permission(format!(
+                "You can't use `/{}`: {reason}.",
+                invocation.command
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Reflects `checks` in the registration of a command.
+///
+/// Permission checks become `default_member_permissions`, and every check that can only pass
+/// in a guild disables the command in DMs.
+pub fn apply_checks(mut command: CreateCommand, checks: &[Check]) -> CreateCommand {
+    let permissions = checks
+        .iter()
+        .filter_map(|check| match check {
+            Check::Permissions(permissions) => Some(*permissions),
+            _ => None,
+        })
+        .fold(Permissions::empty(), |acc, p| acc | p);
+    if !permissions.is_empty() {
+        command = command.default_member_permissions(permissions);
+    }
+
+    let guild_only = checks
+        .iter()
+        .any(|check| matches!(check, Check::GuildOnly | Check::Permissions(_) | Check::Role(_)));
+    if guild_only {
+        command = command.dm_permission(false);
+    }
+    command
+}
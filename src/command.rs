@@ -1,15 +1,41 @@
+use std::any::{type_name, TypeId};
+use std::time::Duration;
+
 use serenity::all::*;
 use async_trait::async_trait;
+use crate::args::CommandArgs;
+use crate::checks::{apply_checks, run_checks, Check};
+use crate::cooldown::{hit_cooldown, Cooldown};
+use crate::error::{CommandError, CommandResult};
+use crate::i18n::{in_scope, localize_command};
+use crate::invocation::Invocation;
+use crate::registration::extension_kind;
+use crate::registry::registry;
+use crate::response::{Defer, Responder};
+use crate::scope::CommandScope;
+use crate::subcommand::{find_subcommand, CommandNode};
+use crate::validate::ValidationError;
+
+// Only used by the `fun` and `utility` commands.
+#[cfg_attr(not(any(feature = "fun", feature = "utility")), allow(unused_imports))]
+pub use discord_bot_macros::slash_command;
 
 /// A trait that defines a global slash command for a Discord bot using Serenity.
 ///
 /// Each struct implementing this trait can be dynamically registered and executed.
 /// Useful in modular bot architectures.
 ///
-/// Use the `register_slash_command!` macro to automatically register the command
+/// Use `register!(MyCommand as SlashCommand)` to automatically register the command
 /// via the inventory system.
 #[async_trait]
 pub trait SlashCommand: Sync + Send {
+    /// The typed arguments of this command.
+    ///
+    /// Use a struct deriving `CommandArgs` for commands with options, or `()` for none.
+    /// The arguments are parsed before `run` is called; if parsing fails the user
+    /// gets an ephemeral error message and `run` is not called.
+    type Args: CommandArgs;
+
     /// The name of the slash command (e.g. `"ping"`).
     ///
     /// This will be the trigger used by users, such as `/ping`.
@@ -20,75 +46,298 @@ pub trait SlashCommand: Sync + Send {
     /// This is shown in the Discord client when browsing commands.
     fn description(&self) -> &'static str;
 
+    /// (Optional) The category this command is listed under in `/help`, e.g. `"Moderation"`.
+    ///
+    /// Commands in `src/commands/{module}/` use their module name (`super::MODULE`), so that
+    /// disabling the module with `DISABLED_MODULES` leaves them out. Default is `"General"`,
+    /// which can't be disabled.
+    fn category(&self) -> &'static str {
+        "General"
+    }
+
+    /// (Optional) Returns the subcommands and subcommand groups of this command.
+    ///
+    /// When this is not empty, the command is registered as a tree (e.g. `/config get`,
+    /// `/config set`) and every invocation is routed to the matching subcommand:
+    /// `Args` must then be `()` (the registry refuses to build otherwise) and `run` is never called.
+    ///
+    /// Default is an empty list (a flat command).
+    fn subcommands(&self) -> Vec<CommandNode> {
+        vec![]
+    }
+
     /// (Optional) Returns the list of command options (parameters) used by this command.
     ///
-    /// Override this if your command uses options like strings, integers, booleans, etc.
     /// These will be included in the `register()` method automatically.
     ///
-    /// Default is an empty list (no options).
+    /// Default is the subcommands if there are any, otherwise the options declared by `Self::Args`.
     fn options(&self) -> Vec<CreateCommandOption> {
+        let subcommands = self.subcommands();
+        if subcommands.is_empty() {
+            Self::Args::options()
+        } else {
+            subcommands.iter().map(CommandNode::register).collect()
+        }
+    }
+
+    /// (Optional) Where this command is registered: globally, in specific guilds,
+    /// or only in the development guild.
+    ///
+    /// Default is `CommandScope::Global`.
+    fn scope(&self) -> CommandScope {
+        CommandScope::Global
+    }
+
+    /// (Optional) Returns the checks that must pass before this command runs,
+    /// such as `Check::GuildOnly` or `Check::Permissions(Permissions::MANAGE_MESSAGES)`.
+    ///
+    /// They apply to every subcommand too. Default is no checks.
+    fn checks(&self) -> Vec<Check> {
         vec![]
     }
 
+    /// (Optional) Returns how often this command may be used, e.g.
+    /// `Cooldown::new(CooldownBucket::User, 3, Duration::from_secs(60))`.
+    ///
+    /// The cooldown is shared by every subcommand and only counts invocations that
+    /// passed the checks. Default is no cooldown.
+    fn cooldown(&self) -> Option<Cooldown> {
+        None
+    }
+
+    /// (Optional) When the response to this command is deferred.
+    ///
+    /// Default is `Defer::AfterDelay { ephemeral: false }`: the dispatcher defers the
+    /// response if `run` hasn't responded after 2.5 seconds. Use `Defer::Never` for commands
+    /// that open a modal after a slow operation.
+    fn defer(&self) -> Defer {
+        Defer::default()
+    }
+
     /// Defines how this command should be registered on Discord.
     ///
-    /// This uses `name()`, `description()`, and `options()` by default.
+    /// This uses `name()`, `description()`, `options()` and `checks()` by default, with the
+    /// translations of the `commands.{name}` keys of the translation files.
     /// You can override this if you need advanced customization.
     fn register(&self) -> CreateCommand {
-        CreateCommand::new(self.name())
+        let command = CreateCommand::new(self.name())
             .description(self.description())
-            .set_options(self.options())
+            .set_options(in_scope(self.name(), || self.options()));
+        apply_checks(localize_command(command, self.name()), &self.checks())
+    }
+
+    /// (Optional) Whether this command can also be used as a prefix command (e.g. `!sum 1 2`),
+    /// when prefix commands are enabled. See `prefix`.
+    ///
+    /// Default is `true`. Commands that need an interaction, such as the ones showing a modal,
+    /// should return `false`.
+    fn prefix(&self) -> bool {
+        true
     }
 
     /// The logic to be executed when this command is invoked.
     ///
     /// # Arguments
     /// * `ctx` - The bot context provided by Serenity.
-    /// * `interaction` - The interaction object representing the command usage.
-    async fn run(&self, ctx: &Context, interaction: &CommandInteraction);
-}
+    /// * `invocation` - Who used the command and where, through an interaction or a prefix command message.
+    /// * `response` - The handle to respond with, which knows whether the response was deferred.
+    /// * `args` - The parsed arguments of the command.
+    ///
+    /// Returning an error sends an ephemeral error message to the user and logs it.
+    /// It is never called for commands made of subcommands, which implement it with `unreachable!`.
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: Self::Args) -> CommandResult;
 
-/// A helper trait to provide a static reference to an instance of the command.
-pub trait HasInstance {
-    const INSTANCE: Self;
+    /// (Optional) Returns autocomplete suggestions for the option the user is typing.
+    ///
+    /// Called for options registered with autocomplete enabled (`#[arg(autocomplete)]`).
+    /// `focused` holds the name of the option and its partial input; the values of the
+    /// other options can be read from `interaction`. At most 25 choices are sent.
+    ///
+    /// Default is no suggestions.
+    async fn autocomplete(
+        &self,
+        _ctx: &Context,
+        _interaction: &CommandInteraction,
+        _focused: AutocompleteOption<'_>,
+    ) -> Vec<AutocompleteChoice> {
+        vec![]
+    }
 }
 
-/// Macro to register a struct that implements `SlashCommand` and `HasInstance`.
+/// How long autocomplete handlers may take before an empty list is sent instead.
 ///
-/// Usage:
-/// ```
-/// register_slash_command!(MyCommandStruct);
-/// ```
-#[macro_export]
-macro_rules! register_slash_command {
-    ($command:ty) => {
-        inventory::submit! {
-            &< $command as $crate::command::HasInstance >::INSTANCE
-                as &'static (dyn $crate::command::SlashCommand + Sync + Send)
-        }
-    };
+/// Discord discards autocomplete responses sent more than three seconds after the interaction.
+const AUTOCOMPLETE_DEADLINE: Duration = Duration::from_millis(2500);
+
+/// Object-safe view of a `SlashCommand`, used by the registry and the dispatcher.
+///
+/// This is implemented automatically for every `SlashCommand`.
+#[async_trait]
+pub trait DynSlashCommand: Sync + Send {
+    /// See `SlashCommand::name`.
+    fn name(&self) -> &'static str;
+
+    /// See `SlashCommand::description`.
+    fn description(&self) -> &'static str;
+
+    /// See `SlashCommand::category`.
+    fn category(&self) -> &'static str;
+
+    /// See `SlashCommand::scope`.
+    fn scope(&self) -> CommandScope;
+
+    /// See `SlashCommand::checks`.
+    fn checks(&self) -> Vec<Check>;
+
+    /// See `SlashCommand::cooldown`.
+    fn cooldown(&self) -> Option<Cooldown>;
+
+    /// See `SlashCommand::prefix`.
+    fn prefix(&self) -> bool;
+
+    /// See `SlashCommand::register`.
+    fn register(&self) -> CreateCommand;
+
+    /// A mistake in how the command is declared that Discord wouldn't catch: a command
+    /// with subcommands whose `Args` isn't `()`, since those arguments would never be parsed.
+    fn declaration_error(&self) -> Option<ValidationError>;
+
+    /// Evaluates the checks and the cooldown, parses the arguments in `data` and runs the command with them.
+    ///
+    /// `data` is the data of the interaction, or the one parsed from a prefix command message.
+    async fn dispatch(
+        &self,
+        ctx: &Context,
+        invocation: &Invocation<'_>,
+        data: &CommandData,
+        response: &Responder<'_>,
+    ) -> CommandResult;
+
+    /// Routes an autocomplete `interaction` to the command (or subcommand) owning the
+    /// focused option and responds with its suggestions.
+    async fn dispatch_autocomplete(&self, ctx: &Context, interaction: &CommandInteraction);
 }
 
-// Collect all registered slash commands from inventory
-inventory::collect!(&'static (dyn SlashCommand + Sync + Send));
+#[async_trait]
+impl<T: SlashCommand> DynSlashCommand for T
+where
+    T::Args: 'static,
+{
+    fn name(&self) -> &'static str {
+        SlashCommand::name(self)
+    }
+
+    fn description(&self) -> &'static str {
+        SlashCommand::description(self)
+    }
+
+    fn category(&self) -> &'static str {
+        SlashCommand::category(self)
+    }
 
-/// Returns a list of all slash commands registered in the inventory.
-pub fn all_slash_commands() -> Vec<&'static (dyn SlashCommand + Sync + Send)> {
-    inventory::iter::<&'static (dyn SlashCommand + Sync + Send)>
-        .into_iter()
-        .copied()
-        .collect()
+    fn scope(&self) -> CommandScope {
+        SlashCommand::scope(self)
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        SlashCommand::checks(self)
+    }
+
+    fn cooldown(&self) -> Option<Cooldown> {
+        SlashCommand::cooldown(self)
+    }
+
+    fn prefix(&self) -> bool {
+        SlashCommand::prefix(self)
+    }
+
+    fn register(&self) -> CreateCommand {
+        SlashCommand::register(self)
+    }
+
+    fn declaration_error(&self) -> Option<ValidationError> {
+        (TypeId::of::<T::Args>() != TypeId::of::<()>() && !self.subcommands().is_empty()).then(|| ValidationError {
+            location: format!("/{}", SlashCommand::name(self)),
+            problem: format!("has subcommands, so its `Args` must be `()`, not `{}`", type_name::<T::Args>()),
+        })
+    }
+
+    async fn dispatch(
+        &self,
+        ctx: &Context,
+        invocation: &Invocation<'_>,
+        data: &CommandData,
+        response: &Responder<'_>,
+    ) -> CommandResult {
+        let options = data.options();
+        let subcommands = self.subcommands();
+        run_checks(&SlashCommand::checks(self), invocation)?;
+
+        if subcommands.is_empty() {
+            let args = T::Args::parse(&options)?;
+            if let Some(cooldown) = SlashCommand::cooldown(self) {
+                hit_cooldown(SlashCommand::name(self), &cooldown, invocation)?;
+            }
+            response.with_defer(self.defer(), self.run(ctx, invocation, response, args)).await
+        } else if let Some((subcommand, sub_options)) = find_subcommand(&subcommands, &options) {
+            run_checks(&subcommand.checks(), invocation)?;
+            if let Some(cooldown) = SlashCommand::cooldown(self) {
+                hit_cooldown(SlashCommand::name(self), &cooldown, invocation)?;
+            }
+            response.with_defer(self.defer(), subcommand.dispatch(ctx, invocation, response, sub_options)).await
+        } else {
+            Err(CommandError::internal(format!("unknown subcommand invoked for /{}", data.name)))
+        }
+    }
+
+    async fn dispatch_autocomplete(&self, ctx: &Context, interaction: &CommandInteraction) {
+        let Some(focused) = interaction.data.autocomplete() else {
+            return;
+        };
+        let options = interaction.data.options();
+        let subcommands = self.subcommands();
+
+        let choices = if subcommands.is_empty() {
+            SlashCommand::autocomplete(self, ctx, interaction, focused)
+        } else if let Some((subcommand, _)) = find_subcommand(&subcommands, &options) {
+            subcommand.autocomplete(ctx, interaction, focused)
+        } else {
+            return;
+        };
+
+        let mut choices = match tokio::time::timeout(AUTOCOMPLETE_DEADLINE, choices).await {
+            Ok(choices) => choices,
+            Err(_) => {
+                eprintln!("Autocomplete for /{} timed out", interaction.data.name);
+                vec![]
+            }
+        };
+        choices.truncate(25);
+
+        let _ = interaction.create_response(
+            ctx,
+            CreateInteractionResponse::Autocomplete(
+                CreateAutocompleteResponse::new().set_choices(choices),
+            ),
+        ).await;
+    }
 }
 
-/// Registers all collected slash commands globally with Discord.
-///
-/// This will call `register()` on each command, which now includes name, description, and options.
-pub async fn register_global_slash_commands(ctx: &Context) -> Result<(), serenity::Error> {
-    let commands: Vec<CreateCommand> = all_slash_commands()
-        .iter()
-        .map(|cmd| cmd.register())
-        .collect();
-
-    Command::set_global_commands(&ctx.http, commands).await?;
-    Ok(())
+
+extension_kind!(
+    /// Returns a list of all slash commands registered in the inventory.
+    pub fn all_slash_commands() -> DynSlashCommand
+);
+
+/// Routes a slash command interaction (or a parsed prefix command) to the command it invokes.
+pub async fn dispatch_slash_command(
+    ctx: &Context,
+    invocation: &Invocation<'_>,
+    data: &CommandData,
+    response: &Responder<'_>,
+) -> CommandResult {
+    match registry().slash_command(&data.name) {
+        Some(cmd) => cmd.dispatch(ctx, invocation, data, response).await,
+        None => Err(CommandError::internal(format!("no slash command named /{}", data.name))),
+    }
 }
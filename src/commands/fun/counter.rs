@@ -0,0 +1,64 @@
+use crate::command::SlashCommand;
+use crate::registration::HasInstance;
+use crate::error::CommandResult;
+use crate::component::{ComponentHandler, ComponentId};
+use crate::invocation::Invocation;
+use crate::response::{Reply, Responder};
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::register;
+
+pub struct CounterCommand;
+
+impl HasInstance for CounterCommand {
+    const INSTANCE: Self = CounterCommand;
+}
+
+/// Builds the text showing `count`.
+fn counter_content(count: u64) -> String {
+    format!("Count: **{count}**")
+}
+
+/// Builds the increment and reset buttons of a counter at `count`.
+fn counter_buttons(count: u64) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(ComponentId::build("counter", "increment", &[&count])).label("+1"),
+        CreateButton::new(ComponentId::build("counter", "reset", &[]))
+            .label("Reset")
+            .style(ButtonStyle::Secondary),
+    ])]
+}
+
+#[async_trait]
+impl SlashCommand for CounterCommand {
+    type Args = ();
+
+    fn name(&self) -> &'static str { "counter" }
+    fn description(&self) -> &'static str { "Posts a counter with buttons" }
+    fn category(&self) -> &'static str { super::MODULE }
+    async fn run(&self, _ctx: &Context, _invocation: &Invocation<'_>, response: &Responder<'_>, _args: ()) -> CommandResult {
+        response.send(Reply::new(counter_content(0)).components(counter_buttons(0))).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ComponentHandler for CounterCommand {
+    fn namespace(&self) -> &'static str { "counter" }
+    fn category(&self) -> &'static str { super::MODULE }
+    fn command(&self) -> Option<&'static str> { Some("counter") }
+    async fn handle(&self, ctx: &Context, interaction: &ComponentInteraction, id: ComponentId<'_>) -> CommandResult {
+        let count = match id.action {
+            "increment" => id.arg::<u64>(0)? + 1,
+            _ => 0,
+        };
+        let message = CreateInteractionResponseMessage::new()
+            .content(counter_content(count))
+            .components(counter_buttons(count));
+        interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(message)).await?;
+        Ok(())
+    }
+}
+
+register!(CounterCommand as SlashCommand);
+register!(CounterCommand as ComponentHandler);
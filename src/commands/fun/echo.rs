@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+use crate::command::slash_command;
+use crate::cooldown::{Cooldown, CooldownBucket};
+use crate::error::CommandResult;
+use crate::invocation::Invocation;
+use crate::response::{Reply, Responder};
+use serenity::all::*;
+
+/// Repeats a message
+#[slash_command(category = super::MODULE, cooldown = Cooldown::new(CooldownBucket::User, 3, Duration::from_secs(10)))]
+async fn echo(
+    _ctx: &Context,
+    _invocation: &Invocation<'_>,
+    response: &Responder<'_>,
+    #[arg(description = "The message to repeat", max_length = 2000)] text: String,
+    #[arg(description = "Only show it to you")] private: Option<bool>,
+) -> CommandResult {
+    response.send(Reply::new(text).ephemeral(private.unwrap_or(false))).await?;
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use crate::args::CommandArgs;
+use crate::command::SlashCommand;
+use crate::registration::HasInstance;
+use crate::error::CommandResult;
+use crate::invocation::Invocation;
+use crate::response::Responder;
+use crate::subcommand::{CommandNode, Subcommand};
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::register;
+
+pub struct MathCommand;
+
+impl HasInstance for MathCommand {
+    const INSTANCE: Self = MathCommand;
+}
+
+#[async_trait]
+impl SlashCommand for MathCommand {
+    type Args = ();
+
+    fn name(&self) -> &'static str { "math" }
+    fn description(&self) -> &'static str { "Small calculations" }
+    fn category(&self) -> &'static str { super::MODULE }
+    fn subcommands(&self) -> Vec<CommandNode> {
+        vec![
+            CommandNode::Subcommand(&MultiplyCommand),
+            CommandNode::group("convert", "Convert temperatures", vec![&CelsiusCommand, &FahrenheitCommand]),
+        ]
+    }
+    async fn run(&self, _ctx: &Context, _invocation: &Invocation<'_>, _response: &Responder<'_>, _args: ()) -> CommandResult {
+        unreachable!("/math is dispatched to its subcommands")
+    }
+}
+
+pub struct MultiplyCommand;
+
+#[derive(CommandArgs)]
+pub struct MultiplyArgs {
+    #[arg(description = "First number")]
+    a: f64,
+    #[arg(description = "Second number")]
+    b: f64,
+}
+
+#[async_trait]
+impl Subcommand for MultiplyCommand {
+    type Args = MultiplyArgs;
+
+    fn name(&self) -> &'static str { "multiply" }
+    fn description(&self) -> &'static str { "Multiplies two numbers" }
+    async fn run(&self, _ctx: &Context, _invocation: &Invocation<'_>, response: &Responder<'_>, args: MultiplyArgs) -> CommandResult {
+        response.send(format!("Result: {}", args.a * args.b)).await?;
+        Ok(())
+    }
+}
+
+#[derive(CommandArgs)]
+pub struct TemperatureArgs {
+    #[arg(description = "The temperature to convert", autocomplete)]
+    value: f64,
+}
+
+/// Suggests well-known temperatures whose value starts with what the user typed.
+fn suggest_temperatures(known: &[(&str, f64)], focused: AutocompleteOption<'_>) -> Vec<AutocompleteChoice> {
+    known
+        .iter()
+        .filter(|(_, value)| value.to_string().starts_with(focused.value))
+        .map(|(label, value)| AutocompleteChoice::new(format!("{value} ({label})"), *value))
+        .collect()
+}
+
+pub struct CelsiusCommand;
+
+#[async_trait]
+impl Subcommand for CelsiusCommand {
+    type Args = TemperatureArgs;
+
+    fn name(&self) -> &'static str { "celsius" }
+    fn description(&self) -> &'static str { "Converts Celsius to Fahrenheit" }
+    async fn run(&self, _ctx: &Context, _invocation: &Invocation<'_>, response: &Responder<'_>, args: TemperatureArgs) -> CommandResult {
+        let fahrenheit = args.value * 9.0 / 5.0 + 32.0;
+        response.send(format!("{}°C = {:.1}°F", args.value, fahrenheit)).await?;
+        Ok(())
+    }
+    async fn autocomplete(&self, _ctx: &Context, _interaction: &CommandInteraction, focused: AutocompleteOption<'_>) -> Vec<AutocompleteChoice> {
+        suggest_temperatures(&[("water freezes", 0.0), ("body temperature", 37.0), ("water boils", 100.0)], focused)
+    }
+}
+
+pub struct FahrenheitCommand;
+
+#[async_trait]
+impl Subcommand for FahrenheitCommand {
+    type Args = TemperatureArgs;
+
+    fn name(&self) -> &'static str { "fahrenheit" }
+    fn description(&self) -> &'static str { "Converts Fahrenheit to Celsius" }
+    async fn run(&self, _ctx: &Context, _invocation: &Invocation<'_>, response: &Responder<'_>, args: TemperatureArgs) -> CommandResult {
+        let celsius = (args.value - 32.0) * 5.0 / 9.0;
+        response.send(format!("{}°F = {:.1}°C", args.value, celsius)).await?;
+        Ok(())
+    }
+    async fn autocomplete(&self, _ctx: &Context, _interaction: &CommandInteraction, focused: AutocompleteOption<'_>) -> Vec<AutocompleteChoice> {
+        suggest_temperatures(&[("water freezes", 32.0), ("body temperature", 98.6), ("water boils", 212.0)], focused)
+    }
+}
+
+register!(MathCommand as SlashCommand);
@@ -0,0 +1,9 @@
+//! Games and toys: `/counter`, `/echo`, `/math` and `/sum`.
+
+/// The name of the module, used as the category of its commands.
+pub const MODULE: &str = "Fun";
+
+pub mod counter;
+pub mod echo;
+pub mod math;
+pub mod sum;
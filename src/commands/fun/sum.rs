@@ -0,0 +1,39 @@
+use crate::args::CommandArgs;
+use crate::command::SlashCommand;
+use crate::registration::HasInstance;
+use crate::error::{CommandError, CommandResult};
+use crate::invocation::Invocation;
+use crate::response::Responder;
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::register;
+
+pub struct SumCommand;
+
+#[derive(CommandArgs)]
+pub struct SumArgs {
+    #[arg(description = "First number")]
+    a: i64,
+    #[arg(description = "Second number")]
+    b: i64,
+}
+
+impl HasInstance for SumCommand {
+    const INSTANCE: Self = SumCommand;
+}
+
+#[async_trait]
+impl SlashCommand for SumCommand {
+    type Args = SumArgs;
+
+    fn name(&self) -> &'static str { "sum" }
+    fn description(&self) -> &'static str { "Adds two numbers" }
+    fn category(&self) -> &'static str { super::MODULE }
+    async fn run(&self, _ctx: &Context, _invocation: &Invocation<'_>, response: &Responder<'_>, args: SumArgs) -> CommandResult {
+        let result = args.a.checked_add(args.b).ok_or_else(|| CommandError::user("The result is too large."))?;
+        response.send(response.tr_with("sum.result", &[("result", &result)])).await?;
+        Ok(())
+    }
+}
+
+register!(SumCommand as SlashCommand);
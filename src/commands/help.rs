@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use crate::args::{kind_name, CommandArgs};
+use crate::command::{DynSlashCommand, SlashCommand};
+use crate::component::{ComponentHandler, ComponentId};
+use crate::config::CONFIG;
+use crate::error::{CommandError, CommandResult};
+use crate::guild_settings::{guild_settings, GuildSettings};
+use crate::invocation::Invocation;
+use crate::registration::HasInstance;
+use crate::registry::registry;
+use crate::response::{Reply, Responder};
+use crate::state::StateExt;
+use crate::style::{truncate_field, EmbedStyle};
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::register;
+
+/// How many commands are listed per page of the overview.
+const PAGE_SIZE: usize = 10;
+
+pub struct HelpCommand;
+
+#[derive(CommandArgs)]
+pub struct HelpArgs {
+    #[arg(description = "The command to show the details of", autocomplete)]
+    command: Option<String>,
+}
+
+impl HasInstance for HelpCommand {
+    const INSTANCE: Self = HelpCommand;
+}
+
+/// A command as listed by `/help`: a flat command, or one subcommand of a command tree.
+struct Entry {
+    /// The full name, e.g. `math convert celsius`.
+    name: String,
+    description: String,
+    /// The registered options, as JSON.
+    options: Vec<Value>,
+}
+
+/// The entries of `command`, read from its registration so they match what Discord shows.
+fn entries(command: &dyn DynSlashCommand) -> Vec<Entry> {
+    let json = serde_json::to_value(command.register()).unwrap_or_default();
+    let mut entries = Vec::new();
+    collect_entries(command.name().to_string(), &json, &mut entries);
+    entries
+}
+
+fn collect_entries(name: String, json: &Value, entries: &mut Vec<Entry>) {
+    let options = json.get("options").and_then(Value::as_array).cloned().unwrap_or_default();
+    let is_subcommand = |option: &Value| matches!(option.get("type").and_then(Value::as_u64), Some(1 | 2));
+    if options.iter().any(is_subcommand) {
+        for subcommand in options.iter().filter(|option| is_subcommand(option)) {
+            let sub_name = subcommand.get("name").and_then(Value::as_str).unwrap_or_default();
+            collect_entries(format!("{name} {sub_name}"), subcommand, entries);
+        }
+    } else {
+        let description = json.get("description").and_then(Value::as_str).unwrap_or_default();
+        entries.push(Entry { name, description: description.to_string(), options });
+    }
+}
+
+/// The commands registered where `guild_id` is (`None` for DMs) and not disabled by its
+/// `settings`, sorted by name.
+fn visible_commands(guild_id: Option<GuildId>, settings: &GuildSettings) -> Vec<&'static (dyn DynSlashCommand + Sync + Send)> {
+    registry()
+        .slash_commands()
+        .into_iter()
+        .filter(|command| command.scope().available_in(guild_id) && settings.is_enabled(command.name()))
+        .collect()
+}
+
+/// The pages of the overview: every category, split into pages of `PAGE_SIZE` commands.
+fn overview_pages(guild_id: Option<GuildId>, settings: &GuildSettings) -> Vec<(&'static str, Vec<String>)> {
+    let mut categories: BTreeMap<&'static str, Vec<String>> = BTreeMap::new();
+    for command in visible_commands(guild_id, settings) {
+        categories
+            .entry(command.category())
+            .or_default()
+            .extend(entries(command).iter().map(|entry| format!("`/{}` — {}", entry.name, entry.description)));
+    }
+    categories
+        .into_iter()
+        .flat_map(|(category, lines)| {
+            lines.chunks(PAGE_SIZE).map(|chunk| (category, chunk.to_vec())).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Builds page `page` of the overview, with the buttons to the previous and next pages.
+fn overview(style: &EmbedStyle, guild_id: Option<GuildId>, settings: &GuildSettings, page: usize) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let pages = overview_pages(guild_id, settings);
+    let page = page.min(pages.len().saturating_sub(1));
+    let Some((category, lines)) = pages.get(page) else {
+        return (style.embed().title("📖 Help").description("No commands are available here."), vec![]);
+    };
+
+    let embed = style
+        .embed()
+        .title(format!("📖 Help — {category} ({}/{})", page + 1, pages.len()))
+        .description(format!("{}\n\nUse `/help command:<name>` for the details of a command.", lines.join("\n")));
+    if pages.len() == 1 {
+        return (embed, vec![]);
+    }
+    let buttons = CreateActionRow::Buttons(vec![
+        CreateButton::new(ComponentId::build("help", "page", &[&page.saturating_sub(1)]))
+            .label("◀")
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0),
+        CreateButton::new(ComponentId::build("help", "page", &[&(page + 1)]))
+            .label("▶")
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= pages.len()),
+    ]);
+    (embed, vec![buttons])
+}
+
+/// Describes how to type an entry, e.g. `/sum <a> <b>` (optional options in brackets).
+fn usage(entry: &Entry) -> String {
+    let mut usage = format!("`/{}", entry.name);
+    for option in &entry.options {
+        let name = option.get("name").and_then(Value::as_str).unwrap_or_default();
+        if option.get("required").and_then(Value::as_bool).unwrap_or(false) {
+            usage.push_str(&format!(" <{name}>"));
+        } else {
+            usage.push_str(&format!(" [{name}]"));
+        }
+    }
+    usage.push('`');
+    usage
+}
+
+/// Describes an option, e.g. `` `a` — First number (an integer, required) ``.
+fn describe_option(option: &Value) -> String {
+    let name = option.get("name").and_then(Value::as_str).unwrap_or_default();
+    let description = option.get("description").and_then(Value::as_str).unwrap_or_default();
+    let kind = option.get("type").and_then(Value::as_u64).and_then(|kind| u8::try_from(kind).ok()).unwrap_or_default();
+
+    let mut details = vec![kind_name(CommandOptionType::from(kind)).to_string()];
+    if option.get("required").and_then(Value::as_bool).unwrap_or(false) {
+        details.push("required".to_string());
+    }
+    if let Some(choices) = option.get("choices").and_then(Value::as_array) {
+        let names: Vec<_> = choices.iter().filter_map(|c| c.get("name")?.as_str()).collect();
+        details.push(format!("one of `{}`", names.join("`, `")));
+    }
+    format!("`{name}` — {description} ({})", details.join(", "))
+}
+
+/// Builds the details of `command`: usage and options of every entry, requirements and cooldown.
+fn details(style: &EmbedStyle, command: &dyn DynSlashCommand) -> CreateEmbed {
+    let mut embed = style
+        .embed()
+        .title(format!("📖 /{}", command.name()))
+        .description(command.description());
+
+    let entries = entries(command);
+    let is_tree = entries.len() > 1 || entries.first().is_some_and(|entry| entry.name != command.name());
+    for entry in &entries {
+        let mut lines = vec![usage(entry)];
+        if is_tree {
+            lines.push(entry.description.clone());
+        }
+        lines.extend(entry.options.iter().map(describe_option));
+        let title = if is_tree { format!("/{}", entry.name) } else { "Usage".to_string() };
+        embed = embed.field(title, truncate_field(lines.join("\n")), false);
+    }
+
+    let checks = command.checks();
+    let requirements = if checks.is_empty() {
+        "Everyone".to_string()
+    } else {
+        checks.iter().map(|check| format!("• {check}")).collect::<Vec<_>>().join("\n")
+    };
+    let cooldown = command.cooldown().map_or("None".to_string(), |cooldown| cooldown.to_string());
+    embed = embed
+        .field("Category", command.category(), true)
+        .field("Requirements", requirements, true)
+        .field("Cooldown", cooldown, true);
+    if let Some(prefix) = &CONFIG.prefix
+        && command.prefix()
+    {
+        embed = embed.field("Prefix command", format!("`{prefix}{}`", command.name()), true);
+    }
+    embed
+}
+
+#[async_trait]
+impl SlashCommand for HelpCommand {
+    type Args = HelpArgs;
+
+    fn name(&self) -> &'static str { "help" }
+    fn description(&self) -> &'static str { "Lists the commands, or shows the details of one" }
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: HelpArgs) -> CommandResult {
+        let style = ctx.state::<EmbedStyle>().await.unwrap_or_default();
+        let settings = guild_settings(ctx, invocation.guild_id).await;
+        let reply = match args.command {
+            Some(name) => {
+                let name = name.trim().trim_start_matches('/').to_lowercase();
+                let command = visible_commands(invocation.guild_id, &settings)
+                    .into_iter()
+                    .find(|command| command.name() == name)
+                    .ok_or_else(|| CommandError::user(format!("There is no `/{name}` command.")))?;
+                Reply::default().embed(details(&style, command))
+            }
+            None => {
+                let (embed, components) = overview(&style, invocation.guild_id, &settings, 0);
+                Reply::default().embed(embed).components(components)
+            }
+        };
+        response.send(reply.ephemeral(true)).await?;
+        Ok(())
+    }
+
+    async fn autocomplete(&self, ctx: &Context, interaction: &CommandInteraction, focused: AutocompleteOption<'_>) -> Vec<AutocompleteChoice> {
+        let typed = focused.value.trim_start_matches('/').to_lowercase();
+        let settings = guild_settings(ctx, interaction.guild_id).await;
+        visible_commands(interaction.guild_id, &settings)
+            .into_iter()
+            .filter(|command| command.name().starts_with(&typed))
+            .map(|command| AutocompleteChoice::new(format!("/{}", command.name()), command.name()))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ComponentHandler for HelpCommand {
+    fn namespace(&self) -> &'static str { "help" }
+    fn command(&self) -> Option<&'static str> { Some("help") }
+    async fn handle(&self, ctx: &Context, interaction: &ComponentInteraction, id: ComponentId<'_>) -> CommandResult {
+        let style = ctx.state::<EmbedStyle>().await.unwrap_or_default();
+        let settings = guild_settings(ctx, interaction.guild_id).await;
+        let (embed, components) = overview(&style, interaction.guild_id, &settings, id.arg(0)?);
+        let message = CreateInteractionResponseMessage::new().embed(embed).components(components);
+        interaction.create_response(ctx, CreateInteractionResponse::UpdateMessage(message)).await?;
+        Ok(())
+    }
+}
+
+register!(HelpCommand as SlashCommand);
+register!(HelpCommand as ComponentHandler);
@@ -0,0 +1,360 @@
+use crate::args::CommandArgs;
+use crate::checks::Check;
+use crate::command::SlashCommand;
+use crate::registration::HasInstance;
+use crate::error::{CommandError, CommandResult};
+use crate::guild_settings::{guild_settings, mentions, AccessChange, AccessRules, GuildSettingsStore};
+use crate::invocation::Invocation;
+use crate::registry::registry;
+use crate::response::{Reply, Responder};
+use crate::scope::sync_guild_commands;
+use crate::state::StateExt;
+use crate::style::truncate_field;
+use crate::subcommand::{CommandNode, Subcommand};
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::register;
+
+/// `/commands`: lets server admins turn commands off and on in their guild, and choose who
+/// may use them and where.
+pub struct CommandsCommand;
+
+impl HasInstance for CommandsCommand {
+    const INSTANCE: Self = CommandsCommand;
+}
+
+#[async_trait]
+impl SlashCommand for CommandsCommand {
+    type Args = ();
+
+    fn name(&self) -> &'static str { "commands" }
+    fn description(&self) -> &'static str { "Manages the commands of this server" }
+    fn checks(&self) -> Vec<Check> {
+        vec![Check::GuildOnly, Check::Permissions(Permissions::MANAGE_GUILD)]
+    }
+    fn subcommands(&self) -> Vec<CommandNode> {
+        vec![
+            CommandNode::Subcommand(&DisableCommand),
+            CommandNode::Subcommand(&EnableCommand),
+            CommandNode::Subcommand(&ListCommand),
+            CommandNode::group(
+                "access",
+                "Chooses who may use a command and where",
+                vec![&AllowCommand, &DenyCommand, &ClearCommand, &ShowCommand],
+            ),
+        ]
+    }
+    async fn run(&self, _ctx: &Context, _invocation: &Invocation<'_>, _response: &Responder<'_>, _args: ()) -> CommandResult {
+        unreachable!("/commands is dispatched to its subcommands")
+    }
+}
+
+/// The name of every command a guild can turn off or restrict: every registered command but
+/// `/commands` itself, so admins can't lock themselves out.
+fn toggleable_commands() -> Vec<&'static str> {
+    let registry = registry();
+    let mut names: Vec<_> = registry
+        .slash_commands()
+        .iter()
+        .map(|cmd| cmd.name())
+        .chain(registry.user_commands().iter().map(|cmd| cmd.name()))
+        .chain(registry.message_commands().iter().map(|cmd| cmd.name()))
+        .filter(|name| *name != "commands")
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Finds the command `typed` refers to, e.g. `sum`, `/sum` or `user info`.
+fn find_command(typed: &str) -> Result<&'static str, CommandError> {
+    let typed = typed.trim().trim_start_matches('/');
+    toggleable_commands()
+        .into_iter()
+        .find(|name| name.eq_ignore_ascii_case(typed))
+        .ok_or_else(|| CommandError::user(format!("There is no `{typed}` command that can be managed.")))
+}
+
+/// Suggests the commands whose name starts with what the user typed and, unless `enabled`
+/// is `None`, that are currently enabled (or not) in the guild.
+async fn suggest_commands(ctx: &Context, interaction: &CommandInteraction, focused: AutocompleteOption<'_>, enabled: Option<bool>) -> Vec<AutocompleteChoice> {
+    let settings = guild_settings(ctx, interaction.guild_id).await;
+    let typed = focused.value.trim_start_matches('/').to_lowercase();
+    toggleable_commands()
+        .into_iter()
+        .filter(|name| enabled.is_none_or(|enabled| settings.is_enabled(name) == enabled))
+        .filter(|name| name.to_lowercase().starts_with(&typed))
+        .take(25)
+        .map(|name| AutocompleteChoice::new(name, name))
+        .collect()
+}
+
+/// Turns the command `typed` refers to on or off in the guild of `invocation`, then updates
+/// the guild's command picker.
+async fn set_enabled(ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, typed: &str, enabled: bool) -> CommandResult {
+    let guild_id = invocation.guild_id.ok_or_else(|| CommandError::user("This only works in servers."))?;
+    let name = find_command(typed)?;
+    let state = if enabled { "enabled" } else { "disabled" };
+
+    let store = ctx.state::<GuildSettingsStore>().await?;
+    let changed = store
+        .update(guild_id, |settings| {
+            if enabled {
+                settings.disabled_commands.remove(name)
+            } else {
+                settings.disabled_commands.insert(name.to_string())
+            }
+        })
+        .await
+        .map_err(CommandError::internal)?;
+    if !changed {
+        return Err(CommandError::user(format!("`{name}` is already {state} in this server.")));
+    }
+
+    println!("{} {state} {name} in guild {guild_id}", invocation.user.name);
+    response.send_ephemeral(format!("✅ `{name}` is now {state} in this server.")).await?;
+    if let Err(err) = sync_guild_commands(ctx, guild_id).await {
+        eprintln!("Error syncing the commands of guild {guild_id}: {err:?}");
+    }
+    Ok(())
+}
+
+#[derive(CommandArgs)]
+pub struct ToggleArgs {
+    #[arg(description = "The name of the command", autocomplete)]
+    command: String,
+}
+
+pub struct DisableCommand;
+
+#[async_trait]
+impl Subcommand for DisableCommand {
+    type Args = ToggleArgs;
+
+    fn name(&self) -> &'static str { "disable" }
+    fn description(&self) -> &'static str { "Turns a command off in this server" }
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: ToggleArgs) -> CommandResult {
+        set_enabled(ctx, invocation, response, &args.command, false).await
+    }
+    async fn autocomplete(&self, ctx: &Context, interaction: &CommandInteraction, focused: AutocompleteOption<'_>) -> Vec<AutocompleteChoice> {
+        suggest_commands(ctx, interaction, focused, Some(true)).await
+    }
+}
+
+pub struct EnableCommand;
+
+#[async_trait]
+impl Subcommand for EnableCommand {
+    type Args = ToggleArgs;
+
+    fn name(&self) -> &'static str { "enable" }
+    fn description(&self) -> &'static str { "Turns a disabled command back on in this server" }
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: ToggleArgs) -> CommandResult {
+        set_enabled(ctx, invocation, response, &args.command, true).await
+    }
+    async fn autocomplete(&self, ctx: &Context, interaction: &CommandInteraction, focused: AutocompleteOption<'_>) -> Vec<AutocompleteChoice> {
+        suggest_commands(ctx, interaction, focused, Some(false)).await
+    }
+}
+
+pub struct ListCommand;
+
+#[async_trait]
+impl Subcommand for ListCommand {
+    type Args = ();
+
+    fn name(&self) -> &'static str { "list" }
+    fn description(&self) -> &'static str { "Lists the commands disabled in this server" }
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, _args: ()) -> CommandResult {
+        let guild_id = invocation.guild_id.ok_or_else(|| CommandError::user("This only works in servers."))?;
+        let settings = ctx.state::<GuildSettingsStore>().await?.get(guild_id);
+        let description = if settings.disabled_commands.is_empty() {
+            "Every command is enabled.".to_string()
+        } else {
+            settings.disabled_commands.iter().map(|name| format!("• `{name}`")).collect::<Vec<_>>().join("\n")
+        };
+        let embed = response.embed().title("🔌 Disabled commands").description(description);
+        response.send(Reply::default().embed(embed).ephemeral(true)).await?;
+        Ok(())
+    }
+}
+
+#[derive(CommandArgs)]
+pub struct AccessArgs {
+    #[arg(description = "The name of the command", autocomplete)]
+    command: String,
+    #[arg(description = "A role")]
+    role: Option<RoleId>,
+    #[arg(description = "A member")]
+    user: Option<UserId>,
+    #[arg(description = "A channel")]
+    channel: Option<ChannelId>,
+}
+
+/// Applies `change` to the roles, members and channels of `args` in the rules of its command,
+/// or removes every rule of the command when clearing without any of them.
+async fn change_access(ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: AccessArgs, change: AccessChange) -> CommandResult {
+    let guild_id = invocation.guild_id.ok_or_else(|| CommandError::user("This only works in servers."))?;
+    let name = find_command(&args.command)?;
+
+    let targets: Vec<String> = [
+        args.role.map(|id| id.mention().to_string()),
+        args.user.map(|id| id.mention().to_string()),
+        args.channel.map(|id| id.mention().to_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if targets.is_empty() && change != AccessChange::Clear {
+        return Err(CommandError::user("Pick a role, a member or a channel."));
+    }
+
+    let store = ctx.state::<GuildSettingsStore>().await?;
+    store
+        .update(guild_id, |settings| {
+            if targets.is_empty() {
+                settings.access.remove(name);
+                return;
+            }
+            let rules = settings.access.entry(name.to_string()).or_default();
+            if let Some(id) = args.role {
+                rules.roles.apply(id, change);
+            }
+            if let Some(id) = args.user {
+                rules.users.apply(id, change);
+            }
+            if let Some(id) = args.channel {
+                rules.channels.apply(id, change);
+            }
+        })
+        .await
+        .map_err(CommandError::internal)?;
+
+    let targets = targets.join(", ");
+    let message = match change {
+        AccessChange::Allow => format!("✅ Allowed `{name}` for {targets}."),
+        AccessChange::Deny => format!("✅ Denied `{name}` to {targets}."),
+        AccessChange::Clear if targets.is_empty() => format!("✅ Removed every access rule of `{name}`."),
+        AccessChange::Clear => format!("✅ Removed the access rules of `{name}` for {targets}."),
+    };
+    println!("{} changed the access rules of {name} in guild {guild_id}: {message}", invocation.user.name);
+    response.send_ephemeral(message).await?;
+    Ok(())
+}
+
+/// Describes the `rules` of a command, one line per kind of rule.
+fn describe_rules(rules: &AccessRules) -> String {
+    let mut lines = vec![];
+    let mut describe = |kind: &str, allowed: String, denied: String| {
+        if !allowed.is_empty() {
+            lines.push(format!("{kind} allowed: {allowed}"));
+        }
+        if !denied.is_empty() {
+            lines.push(format!("{kind} denied: {denied}"));
+        }
+    };
+    describe("Channels", mentions(&rules.channels.allow), mentions(&rules.channels.deny));
+    describe("Members", mentions(&rules.users.allow), mentions(&rules.users.deny));
+    describe("Roles", mentions(&rules.roles.allow), mentions(&rules.roles.deny));
+    lines.join("\n")
+}
+
+pub struct AllowCommand;
+
+#[async_trait]
+impl Subcommand for AllowCommand {
+    type Args = AccessArgs;
+
+    fn name(&self) -> &'static str { "allow" }
+    fn description(&self) -> &'static str { "Allows a role, member or channel to use a command" }
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: AccessArgs) -> CommandResult {
+        change_access(ctx, invocation, response, args, AccessChange::Allow).await
+    }
+    async fn autocomplete(&self, ctx: &Context, interaction: &CommandInteraction, focused: AutocompleteOption<'_>) -> Vec<AutocompleteChoice> {
+        suggest_commands(ctx, interaction, focused, None).await
+    }
+}
+
+pub struct DenyCommand;
+
+#[async_trait]
+impl Subcommand for DenyCommand {
+    type Args = AccessArgs;
+
+    fn name(&self) -> &'static str { "deny" }
+    fn description(&self) -> &'static str { "Denies a command to a role, member or channel" }
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: AccessArgs) -> CommandResult {
+        change_access(ctx, invocation, response, args, AccessChange::Deny).await
+    }
+    async fn autocomplete(&self, ctx: &Context, interaction: &CommandInteraction, focused: AutocompleteOption<'_>) -> Vec<AutocompleteChoice> {
+        suggest_commands(ctx, interaction, focused, None).await
+    }
+}
+
+pub struct ClearCommand;
+
+#[async_trait]
+impl Subcommand for ClearCommand {
+    type Args = AccessArgs;
+
+    fn name(&self) -> &'static str { "clear" }
+    fn description(&self) -> &'static str { "Removes the rules of a role, member or channel, or all of them" }
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: AccessArgs) -> CommandResult {
+        change_access(ctx, invocation, response, args, AccessChange::Clear).await
+    }
+    async fn autocomplete(&self, ctx: &Context, interaction: &CommandInteraction, focused: AutocompleteOption<'_>) -> Vec<AutocompleteChoice> {
+        suggest_commands(ctx, interaction, focused, None).await
+    }
+}
+
+#[derive(CommandArgs)]
+pub struct ShowArgs {
+    #[arg(description = "The name of the command (all of them by default)", autocomplete)]
+    command: Option<String>,
+}
+
+pub struct ShowCommand;
+
+#[async_trait]
+impl Subcommand for ShowCommand {
+    type Args = ShowArgs;
+
+    fn name(&self) -> &'static str { "show" }
+    fn description(&self) -> &'static str { "Shows the access rules of the commands" }
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: ShowArgs) -> CommandResult {
+        let guild_id = invocation.guild_id.ok_or_else(|| CommandError::user("This only works in servers."))?;
+        let only = args.command.as_deref().map(find_command).transpose()?;
+        let settings = ctx.state::<GuildSettingsStore>().await?.get(guild_id);
+
+        // Discord allows 25 fields and 6000 characters per embed; keep room for the rest.
+        const MAX_FIELDS: usize = 25;
+        const MAX_FIELDS_LENGTH: usize = 5000;
+
+        let mut embed = response.embed().title("🔐 Access rules");
+        let rules: Vec<_> = settings.access.iter().filter(|(name, _)| only.is_none_or(|only| only == name.as_str())).collect();
+        let mut length = 0;
+        let mut shown = 0;
+        for (name, rules) in &rules {
+            let title = format!("`{name}`");
+            let value = truncate_field(describe_rules(rules));
+            length += title.chars().count() + value.chars().count();
+            if shown == MAX_FIELDS || length > MAX_FIELDS_LENGTH {
+                break;
+            }
+            embed = embed.field(title, value, false);
+            shown += 1;
+        }
+        if rules.is_empty() {
+            embed = embed.description("No access rules: the commands are open to everyone their permissions let through.");
+        } else if shown < rules.len() {
+            embed = embed.description(format!("{} more commands have rules: pick one with `command:` to see them.", rules.len() - shown));
+        }
+        response.send(Reply::default().embed(embed).ephemeral(true)).await?;
+        Ok(())
+    }
+    async fn autocomplete(&self, ctx: &Context, interaction: &CommandInteraction, focused: AutocompleteOption<'_>) -> Vec<AutocompleteChoice> {
+        suggest_commands(ctx, interaction, focused, None).await
+    }
+}
+
+register!(CommandsCommand as SlashCommand);
@@ -1 +1,47 @@
-pub mod ping;
+//! The commands of the bot, grouped in modules that can be left out of the build with
+//! cargo features, or disabled per deployment with `DISABLED_MODULES`.
+
+#[cfg(feature = "fun")]
+pub mod fun;
+#[cfg(feature = "moderation")]
+pub mod moderation;
+#[cfg(feature = "utility")]
+pub mod utility;
+
+pub mod help;
+pub mod manage_commands;
+
+use crate::config::CONFIG;
+
+/// The modules compiled into this build.
+pub const MODULES: &[&str] = &[
+    #[cfg(feature = "fun")]
+    fun::MODULE,
+    #[cfg(feature = "moderation")]
+    moderation::MODULE,
+    #[cfg(feature = "utility")]
+    utility::MODULE,
+];
+
+/// Whether the commands (and component and modal handlers) of `category` are enabled, i.e. it
+/// isn't a module listed in `DISABLED_MODULES`.
+///
+/// Categories that aren't modules, like `"General"`, are always enabled.
+pub fn is_enabled(category: &str) -> bool {
+    !MODULES.contains(&category) || !CONFIG.disabled_modules.contains(&category.to_lowercase())
+}
+
+/// The modules compiled into this build and not disabled in the configuration.
+pub fn enabled_modules() -> Vec<&'static str> {
+    MODULES.iter().copied().filter(|module| is_enabled(module)).collect()
+}
+
+/// The names in `DISABLED_MODULES` that aren't modules of this build, and so do nothing.
+pub fn unknown_disabled_modules() -> Vec<&'static str> {
+    CONFIG
+        .disabled_modules
+        .iter()
+        .filter(|name| !MODULES.iter().any(|module| module.eq_ignore_ascii_case(name)))
+        .map(String::as_str)
+        .collect()
+}
@@ -0,0 +1,7 @@
+//! Tools for moderators: `/purge` and the "Report message" menu.
+
+/// The name of the module, used as the category of its commands.
+pub const MODULE: &str = "Moderation";
+
+pub mod purge;
+pub mod report_message;
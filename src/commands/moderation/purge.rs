@@ -0,0 +1,61 @@
+use crate::args::CommandArgs;
+use std::time::Duration;
+
+use crate::checks::Check;
+use crate::command::SlashCommand;
+use crate::registration::HasInstance;
+use crate::cooldown::{Cooldown, CooldownBucket};
+use crate::error::{CommandError, CommandResult};
+use crate::invocation::{Invocation, InvocationSource};
+use crate::response::{Defer, Responder};
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::register;
+
+pub struct PurgeCommand;
+
+#[derive(CommandArgs)]
+pub struct PurgeArgs {
+    #[arg(description = "How many messages to delete", min = 1, max = 100)]
+    count: i64,
+}
+
+impl HasInstance for PurgeCommand {
+    const INSTANCE: Self = PurgeCommand;
+}
+
+#[async_trait]
+impl SlashCommand for PurgeCommand {
+    type Args = PurgeArgs;
+
+    fn name(&self) -> &'static str { "purge" }
+    fn description(&self) -> &'static str { "Deletes the latest messages of this channel" }
+    fn category(&self) -> &'static str { super::MODULE }
+    fn checks(&self) -> Vec<Check> {
+        vec![Check::GuildOnly, Check::Permissions(Permissions::MANAGE_MESSAGES)]
+    }
+    fn cooldown(&self) -> Option<Cooldown> {
+        Some(Cooldown::new(CooldownBucket::Channel, 2, Duration::from_secs(30)))
+    }
+    fn defer(&self) -> Defer {
+        Defer::AfterDelay { ephemeral: true }
+    }
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: PurgeArgs) -> CommandResult {
+        let limit = u8::try_from(args.count).map_err(|_| CommandError::user("You can delete up to 100 messages."))?;
+        let mut request = GetMessages::new().limit(limit);
+        // A prefix command message isn't one of the messages to delete.
+        if let InvocationSource::Message(message) = invocation.source {
+            request = request.before(message.id);
+        }
+        let messages = invocation.channel_id.messages(ctx, request).await?;
+        if messages.is_empty() {
+            return Err(CommandError::user("There is nothing to delete here."));
+        }
+        invocation.channel_id.delete_messages(ctx, messages.iter().map(|m| m.id)).await?;
+
+        response.send_ephemeral(format!("🧹 Deleted {} messages.", messages.len())).await?;
+        Ok(())
+    }
+}
+
+register!(PurgeCommand as SlashCommand);
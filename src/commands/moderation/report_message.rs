@@ -0,0 +1,32 @@
+use crate::registration::HasInstance;
+use crate::error::CommandResult;
+use crate::context_menu::MessageCommand;
+use crate::response::Responder;
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::register;
+
+pub struct ReportMessageCommand;
+
+impl HasInstance for ReportMessageCommand {
+    const INSTANCE: Self = ReportMessageCommand;
+}
+
+#[async_trait]
+impl MessageCommand for ReportMessageCommand {
+    fn name(&self) -> &'static str { "Report message" }
+    fn category(&self) -> &'static str { super::MODULE }
+    async fn run(&self, _ctx: &Context, interaction: &CommandInteraction, response: &Responder<'_>, message: &Message) -> CommandResult {
+        println!(
+            "{} reported message {} by {}: {}",
+            interaction.user.name,
+            message.link(),
+            message.author.name,
+            message.content,
+        );
+        response.send_ephemeral("Thanks, the message has been reported to the moderators.").await?;
+        Ok(())
+    }
+}
+
+register!(ReportMessageCommand as MessageCommand);
@@ -0,0 +1,66 @@
+use crate::command::SlashCommand;
+use crate::registration::HasInstance;
+use crate::error::CommandResult;
+use crate::component::ComponentId;
+use crate::modal::{show_modal, ModalBuilder, ModalFieldError, ModalFields, ModalHandler};
+use crate::invocation::Invocation;
+use crate::response::Responder;
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::register;
+
+pub struct FeedbackCommand;
+
+impl HasInstance for FeedbackCommand {
+    const INSTANCE: Self = FeedbackCommand;
+}
+
+#[async_trait]
+impl SlashCommand for FeedbackCommand {
+    type Args = ();
+
+    fn name(&self) -> &'static str { "feedback" }
+    fn description(&self) -> &'static str { "Sends feedback to the bot team" }
+    fn category(&self) -> &'static str { super::MODULE }
+    // The feedback form is a modal, which needs an interaction
+    fn prefix(&self) -> bool { false }
+    async fn run(&self, _ctx: &Context, _invocation: &Invocation<'_>, response: &Responder<'_>, _args: ()) -> CommandResult {
+        let modal = ModalBuilder::new(ComponentId::build("feedback", "submit", &[]), "Send feedback")
+            .short("topic", "Topic")
+            .max_length(100)
+            .paragraph("details", "What would you like to tell us?")
+            .placeholder("Bugs, ideas, anything...")
+            .short("rating", "Rating from 1 to 5")
+            .optional();
+        show_modal(response, modal).await?;
+        Ok(())
+    }
+}
+
+/// Reads the submitted feedback form, returning `(topic, details, rating)`.
+fn parse_feedback(fields: &ModalFields) -> Result<(&str, &str, Option<u8>), ModalFieldError> {
+    Ok((fields.text("topic")?, fields.text("details")?, fields.parse_optional("rating")?))
+}
+
+#[async_trait]
+impl ModalHandler for FeedbackCommand {
+    fn namespace(&self) -> &'static str { "feedback" }
+    fn category(&self) -> &'static str { super::MODULE }
+    fn command(&self) -> Option<&'static str> { Some("feedback") }
+    async fn handle(&self, ctx: &Context, interaction: &ModalInteraction, _id: ComponentId<'_>, fields: ModalFields) -> CommandResult {
+        let (topic, details, rating) = parse_feedback(&fields)?;
+        let rating = rating.map_or("no rating".to_string(), |r| format!("{r}/5"));
+        println!("Feedback from {} ({rating}) about {topic}: {details}", interaction.user.name);
+
+        interaction.create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new().content("Thanks for your feedback!").ephemeral(true),
+            ),
+        ).await?;
+        Ok(())
+    }
+}
+
+register!(FeedbackCommand as SlashCommand);
+register!(FeedbackCommand as ModalHandler);
@@ -0,0 +1,9 @@
+//! Everyday commands: `/feedback`, `/ping`, `/stats` and the "User info" menu.
+
+/// The name of the module, used as the category of its commands.
+pub const MODULE: &str = "Utility";
+
+pub mod feedback;
+pub mod ping;
+pub mod stats;
+pub mod user_info;
@@ -0,0 +1,12 @@
+use crate::command::slash_command;
+use crate::error::CommandResult;
+use crate::invocation::Invocation;
+use crate::response::Responder;
+use serenity::all::*;
+
+/// Replies pong!
+#[slash_command(category = super::MODULE)]
+async fn ping(_ctx: &Context, _invocation: &Invocation<'_>, response: &Responder<'_>) -> CommandResult {
+    response.send(response.tr("ping.pong")).await?;
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use std::sync::atomic::Ordering;
+
+use crate::command::SlashCommand;
+use crate::registration::HasInstance;
+use crate::error::CommandResult;
+use crate::middlewares::stats::BotStats;
+use crate::invocation::Invocation;
+use crate::response::Responder;
+use crate::state::StateExt;
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::register;
+
+pub struct StatsCommand;
+
+impl HasInstance for StatsCommand {
+    const INSTANCE: Self = StatsCommand;
+}
+
+#[async_trait]
+impl SlashCommand for StatsCommand {
+    type Args = ();
+
+    fn name(&self) -> &'static str { "stats" }
+    fn description(&self) -> &'static str { "Shows how long the bot has been up and how many commands it ran" }
+    fn category(&self) -> &'static str { super::MODULE }
+    async fn run(&self, ctx: &Context, _invocation: &Invocation<'_>, response: &Responder<'_>, _args: ()) -> CommandResult {
+        let stats = ctx.state::<BotStats>().await?;
+        let uptime = stats.started.elapsed().as_secs();
+        response
+            .send_embed(|embed| {
+                embed
+                    .title("📊 Bot stats")
+                    .field("Uptime", format!("{}h {}m", uptime / 3600, uptime % 3600 / 60), true)
+                    .field("Commands ran", stats.succeeded.load(Ordering::Relaxed).to_string(), true)
+                    .field("Failed", stats.failed.load(Ordering::Relaxed).to_string(), true)
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+register!(StatsCommand as SlashCommand);
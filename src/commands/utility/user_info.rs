@@ -0,0 +1,41 @@
+use crate::registration::HasInstance;
+use crate::error::CommandResult;
+use crate::context_menu::UserCommand;
+use crate::response::{Reply, Responder};
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::register;
+
+pub struct UserInfoCommand;
+
+impl HasInstance for UserInfoCommand {
+    const INSTANCE: Self = UserInfoCommand;
+}
+
+#[async_trait]
+impl UserCommand for UserInfoCommand {
+    fn name(&self) -> &'static str { "User info" }
+    fn category(&self) -> &'static str { super::MODULE }
+    async fn run(
+        &self,
+        _ctx: &Context,
+        _interaction: &CommandInteraction,
+        response: &Responder<'_>,
+        user: &User,
+        member: Option<&PartialMember>,
+    ) -> CommandResult {
+        let mut embed = response
+            .embed()
+            .title(&user.name)
+            .thumbnail(user.face())
+            .field("ID", format!("`{}`", user.id), true)
+            .field("Account created", format!("<t:{}:R>", user.id.created_at().unix_timestamp()), true);
+        if let Some(joined_at) = member.and_then(|m| m.joined_at) {
+            embed = embed.field("Joined this server", format!("<t:{}:R>", joined_at.unix_timestamp()), true);
+        }
+        response.send(Reply::default().embed(embed).ephemeral(true)).await?;
+        Ok(())
+    }
+}
+
+register!(UserInfoCommand as UserCommand);
@@ -0,0 +1,135 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::commands;
+use crate::error::{CommandError, CommandResult};
+use crate::guild_settings::check_command_access;
+use crate::registration::extension_kind;
+
+/// Separator between the segments of a component `custom_id`.
+pub const CUSTOM_ID_SEPARATOR: char = ':';
+
+/// A trait that handles message component interactions (buttons, select menus).
+///
+/// Components are routed by their `custom_id`, which follows the convention
+/// `namespace:action:arg1:arg2...` (e.g. `poll:vote:42`). Every handler owns one
+/// namespace and receives the parsed id of the component that was used.
+///
+/// Use `register!(MyHandler as ComponentHandler)` to automatically register the handler
+/// via the inventory system.
+#[async_trait]
+pub trait ComponentHandler: Sync + Send {
+    /// The namespace routed to this handler, i.e. the first segment of the `custom_id`.
+    fn namespace(&self) -> &'static str;
+
+    /// (Optional) The category of the command this handler belongs to, see `SlashCommand::category`.
+    ///
+    /// Handlers of a module disabled with `DISABLED_MODULES` are not run. Default is `"General"`.
+    fn category(&self) -> &'static str {
+        "General"
+    }
+
+    /// (Optional) The registered name of the command this handler belongs to, e.g. `"counter"`.
+    ///
+    /// When set, the handler is refused in guilds that turned that command off or whose
+    /// access rules deny it to the user, like the command itself. Default is `None`.
+    fn command(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The logic to be executed when a component of this namespace is used.
+    ///
+    /// # Arguments
+    /// * `ctx` - The bot context provided by Serenity.
+    /// * `interaction` - The component interaction (selected values are in `interaction.data.kind`).
+    /// * `id` - The parsed `custom_id` of the component.
+    async fn handle(&self, ctx: &Context, interaction: &ComponentInteraction, id: ComponentId<'_>) -> CommandResult;
+}
+
+/// A parsed component `custom_id`.
+#[derive(Debug, Clone)]
+pub struct ComponentId<'a> {
+    /// The namespace routing the component to its handler (e.g. `poll`).
+    pub namespace: &'a str,
+    /// The action within the namespace (e.g. `vote`), empty if there is none.
+    pub action: &'a str,
+    /// The remaining segments, carrying the state of the component.
+    pub args: Vec<&'a str>,
+}
+
+impl<'a> ComponentId<'a> {
+    /// Parses a `custom_id` of the form `namespace:action:arg1:arg2...`.
+    pub fn parse(custom_id: &'a str) -> Self {
+        let mut segments = custom_id.split(CUSTOM_ID_SEPARATOR);
+        ComponentId {
+            namespace: segments.next().unwrap_or_default(),
+            action: segments.next().unwrap_or_default(),
+            args: segments.collect(),
+        }
+    }
+
+    /// Builds a `custom_id` from its segments.
+    ///
+    /// Discord limits custom ids to 100 characters, so keep the state small.
+    pub fn build(namespace: &str, action: &str, args: &[&dyn fmt::Display]) -> String {
+        let mut custom_id = format!("{namespace}{CUSTOM_ID_SEPARATOR}{action}");
+        for arg in args {
+            custom_id.push(CUSTOM_ID_SEPARATOR);
+            custom_id.push_str(&arg.to_string());
+        }
+        custom_id
+    }
+
+    /// Parses the argument at `index` as a `T`.
+    pub fn arg<T: FromStr>(&self, index: usize) -> Result<T, ComponentIdError> {
+        let raw = self.args.get(index).ok_or(ComponentIdError::Missing(index))?;
+        raw.parse().map_err(|_| ComponentIdError::Invalid(index, raw.to_string()))
+    }
+}
+
+/// An error raised while reading the state of a component `custom_id`.
+#[derive(Debug, Clone)]
+pub enum ComponentIdError {
+    /// The argument at this index is not present.
+    Missing(usize),
+    /// The argument at this index could not be parsed.
+    Invalid(usize, String),
+}
+
+impl fmt::Display for ComponentIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentIdError::Missing(index) => write!(f, "missing custom_id argument {index}"),
+            ComponentIdError::Invalid(index, raw) => write!(f, "invalid custom_id argument {index}: `{raw}`"),
+        }
+    }
+}
+
+impl std::error::Error for ComponentIdError {}
+
+extension_kind!(
+    /// Returns a list of all component handlers registered in the inventory.
+    pub fn all_component_handlers() -> ComponentHandler
+);
+
+/// Routes a component interaction to the handler owning its namespace.
+pub async fn dispatch_component(ctx: &Context, interaction: &ComponentInteraction) -> CommandResult {
+    let id = ComponentId::parse(&interaction.data.custom_id);
+    match all_component_handlers().into_iter().find(|h| h.namespace() == id.namespace) {
+        Some(handler) if !commands::is_enabled(handler.category()) => {
+            Err(CommandError::user("This feature is disabled."))
+        }
+        Some(handler) => {
+            if let Some(command) = handler.command() {
+                check_command_access(ctx, command, interaction.guild_id, interaction.channel_id, &interaction.user, interaction.member.as_ref()).await?;
+            }
+            handler.handle(ctx, interaction, id).await
+        }
+        None => Err(CommandError::internal(format!(
+            "no component handler for custom_id `{}`",
+            interaction.data.custom_id
+        ))),
+    }
+}
@@ -0,0 +1,73 @@
+use once_cell::sync::Lazy;
+use serenity::all::{GuildId, UserId};
+
+/// Bot-wide settings read from the environment (and `.env`).
+pub struct BotConfig {
+    /// The guild used for development, from `DEV_GUILD_ID`.
+    ///
+    /// When set, every command is registered to this guild only, so changes show up
+    /// instantly instead of waiting for global commands to propagate.
+    pub dev_guild: Option<GuildId>,
+
+    /// The bot owners, from the comma separated `OWNER_IDS`. Used by `Check::OwnerOnly`.
+    pub owners: Vec<UserId>,
+
+    /// Whether commands are blocked for everyone but the owners, from `MAINTENANCE_MODE`
+    /// (`1` or `true`).
+    pub maintenance: bool,
+
+    /// The directory of the translation files, from `LOCALES_DIR` (default `locales`).
+    pub locales_dir: String,
+
+    /// The prefix of prefix commands (e.g. `!`), from `COMMAND_PREFIX`. See `prefix`.
+    pub prefix: Option<String>,
+
+    /// Whether mentioning the bot works as a command prefix, from `MENTION_PREFIX` (`1` or `true`).
+    pub mention_prefix: bool,
+
+    /// The command modules turned off for this deployment, from the comma separated
+    /// `DISABLED_MODULES` (e.g. `fun,moderation`, case-insensitive). See `commands::MODULES`.
+    pub disabled_modules: Vec<String>,
+
+    /// The file the per-guild settings are saved to, from `GUILD_SETTINGS_FILE`
+    /// (default `data/guild_settings.json`). See `guild_settings`.
+    pub guild_settings_file: String,
+}
+
+impl BotConfig {
+    fn from_env() -> Self {
+        BotConfig {
+            dev_guild: std::env::var("DEV_GUILD_ID")
+                .ok()
+                .map(|id| GuildId::new(id.trim().parse().expect("DEV_GUILD_ID must be a guild ID"))),
+            owners: std::env::var("OWNER_IDS")
+                .unwrap_or_default()
+                .split(',')
+                .filter(|id| !id.trim().is_empty())
+                .map(|id| UserId::new(id.trim().parse().expect("OWNER_IDS must be user IDs")))
+                .collect(),
+            maintenance: std::env::var("MAINTENANCE_MODE")
+                .is_ok_and(|value| matches!(value.trim(), "1" | "true")),
+            locales_dir: std::env::var("LOCALES_DIR").unwrap_or_else(|_| "locales".to_string()),
+            prefix: std::env::var("COMMAND_PREFIX")
+                .ok()
+                .map(|prefix| prefix.trim().to_string())
+                .filter(|prefix| !prefix.is_empty()),
+            mention_prefix: std::env::var("MENTION_PREFIX")
+                .is_ok_and(|value| matches!(value.trim(), "1" | "true")),
+            disabled_modules: std::env::var("DISABLED_MODULES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|module| module.trim().to_lowercase())
+                .filter(|module| !module.is_empty())
+                .collect(),
+            guild_settings_file: std::env::var("GUILD_SETTINGS_FILE")
+                .unwrap_or_else(|_| "data/guild_settings.json".to_string()),
+        }
+    }
+}
+
+/// The configuration of the bot, loaded on first use.
+///
+/// `dotenv()` must have been called before this is first accessed.
+pub static CONFIG: Lazy<BotConfig> = Lazy::new(BotConfig::from_env);
@@ -0,0 +1,122 @@
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::error::{CommandError, CommandResult};
+use crate::i18n::localize_menu;
+use crate::registration::extension_kind;
+use crate::registry::registry;
+use crate::response::{Defer, Responder};
+use crate::scope::CommandScope;
+
+/// A trait that defines a user context-menu command (right click on a user > Apps).
+///
+/// Use `register!(MyCommand as UserCommand)` to automatically register the command
+/// via the inventory system. It is registered together with the slash commands of the same scope.
+#[async_trait]
+pub trait UserCommand: Sync + Send {
+    /// The name shown in the context menu (e.g. `"User info"`).
+    ///
+    /// Unlike slash command names, this may contain spaces and capital letters.
+    fn name(&self) -> &'static str;
+
+    /// (Optional) See `SlashCommand::category`. Default is `"General"`.
+    fn category(&self) -> &'static str {
+        "General"
+    }
+
+    /// (Optional) Where this command is registered. Default is `CommandScope::Global`.
+    fn scope(&self) -> CommandScope {
+        CommandScope::Global
+    }
+
+    /// Defines how this command should be registered on Discord, with the translations
+    /// of `commands.{name}.name`.
+    fn register(&self) -> CreateCommand {
+        localize_menu(CreateCommand::new(self.name()).kind(CommandType::User), self.name())
+    }
+
+    /// The logic to be executed when this command is invoked.
+    ///
+    /// # Arguments
+    /// * `ctx` - The bot context provided by Serenity.
+    /// * `interaction` - The interaction object representing the command usage.
+    /// * `response` - The handle to respond with, see `SlashCommand::run`.
+    /// * `user` - The user the command was used on.
+    /// * `member` - Their member data, if the command was used in a guild.
+    async fn run(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        response: &Responder<'_>,
+        user: &User,
+        member: Option<&PartialMember>,
+    ) -> CommandResult;
+}
+
+/// A trait that defines a message context-menu command (right click on a message > Apps).
+///
+/// Use `register!(MyCommand as MessageCommand)` to automatically register the command
+/// via the inventory system. It is registered together with the slash commands of the same scope.
+#[async_trait]
+pub trait MessageCommand: Sync + Send {
+    /// The name shown in the context menu (e.g. `"Report message"`).
+    ///
+    /// Unlike slash command names, this may contain spaces and capital letters.
+    fn name(&self) -> &'static str;
+
+    /// (Optional) See `SlashCommand::category`. Default is `"General"`.
+    fn category(&self) -> &'static str {
+        "General"
+    }
+
+    /// (Optional) Where this command is registered. Default is `CommandScope::Global`.
+    fn scope(&self) -> CommandScope {
+        CommandScope::Global
+    }
+
+    /// Defines how this command should be registered on Discord, with the translations
+    /// of `commands.{name}.name`.
+    fn register(&self) -> CreateCommand {
+        localize_menu(CreateCommand::new(self.name()).kind(CommandType::Message), self.name())
+    }
+
+    /// The logic to be executed when this command is invoked.
+    ///
+    /// # Arguments
+    /// * `ctx` - The bot context provided by Serenity.
+    /// * `interaction` - The interaction object representing the command usage.
+    /// * `response` - The handle to respond with, see `SlashCommand::run`.
+    /// * `message` - The message the command was used on.
+    async fn run(&self, ctx: &Context, interaction: &CommandInteraction, response: &Responder<'_>, message: &Message) -> CommandResult;
+}
+
+extension_kind!(
+    /// Returns a list of all user commands registered in the inventory.
+    pub fn all_user_commands() -> UserCommand
+);
+
+extension_kind!(
+    /// Returns a list of all message commands registered in the inventory.
+    pub fn all_message_commands() -> MessageCommand
+);
+
+/// Routes a user or message command interaction to its command, with the resolved target.
+///
+/// Context-menu commands are deferred like slash commands, with the default `Defer`.
+pub async fn dispatch_context_menu(ctx: &Context, interaction: &CommandInteraction, response: &Responder<'_>) -> CommandResult {
+    let name = interaction.data.name.as_str();
+    match interaction.data.target() {
+        Some(ResolvedTarget::User(user, member)) => {
+            match registry().user_command(name) {
+                Some(cmd) => response.with_defer(Defer::default(), cmd.run(ctx, interaction, response, user, member)).await,
+                None => Err(CommandError::internal(format!("no user command named `{name}`"))),
+            }
+        }
+        Some(ResolvedTarget::Message(message)) => {
+            match registry().message_command(name) {
+                Some(cmd) => response.with_defer(Defer::default(), cmd.run(ctx, interaction, response, message)).await,
+                None => Err(CommandError::internal(format!("no message command named `{name}`"))),
+            }
+        }
+        _ => Err(CommandError::internal(format!("context menu command `{name}` has no resolved target"))),
+    }
+}
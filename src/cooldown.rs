@@ -0,0 +1,234 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use crate::error::{CommandError, CommandResult};
+use crate::invocation::Invocation;
+
+/// What a cooldown is counted per.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CooldownBucket {
+    /// Every user has their own cooldown.
+    User,
+    /// Every channel has its own cooldown, shared by its users.
+    Channel,
+    /// Every guild has its own cooldown, shared by its users (per channel in DMs).
+    Guild,
+    /// One cooldown shared by everyone.
+    Global,
+}
+
+/// A rate limit on a command: at most `uses` invocations per `per`, counted per `bucket`.
+///
+/// `uses` is the burst allowance: a user can fire that many invocations back to back,
+/// and then has to wait for the oldest one to leave the window.
+#[derive(Debug, Clone, Copy)]
+pub struct Cooldown {
+    pub bucket: CooldownBucket,
+    pub uses: usize,
+    pub per: Duration,
+}
+
+impl Cooldown {
+    /// Creates a cooldown allowing `uses` invocations every `per`, counted per `bucket`.
+    pub const fn new(bucket: CooldownBucket, uses: usize, per: Duration) -> Self {
+        Cooldown { bucket, uses, per }
+    }
+
+    /// The key identifying the bucket `invocation` falls into.
+    fn key(&self, invocation: &Invocation<'_>) -> u64 {
+        match self.bucket {
+            CooldownBucket::User => invocation.user.id.get(),
+            CooldownBucket::Channel => invocation.channel_id.get(),
+            CooldownBucket::Guild => invocation
+                .guild_id
+                .map_or(invocation.channel_id.get(), |guild_id| guild_id.get()),
+            CooldownBucket::Global => 0,
+        }
+    }
+}
+
+impl fmt::Display for Cooldown {
+    /// Describes the cooldown for the help, e.g. `3 uses every 10s per user`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uses = if self.uses == 1 { "1 use".to_string() } else { format!("{} uses", self.uses) };
+        let per = match self.bucket {
+            CooldownBucket::User => " per user",
+            CooldownBucket::Channel => " per channel",
+            CooldownBucket::Guild => " per server",
+            CooldownBucket::Global => "",
+        };
+        write!(f, "{uses} every {}{per}", format_wait(self.per))
+    }
+}
+
+/// Above this many tracked buckets, expired ones are swept on the next invocation.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// The recent invocations counted against one bucket.
+#[derive(Default)]
+struct Window {
+    per: Duration,
+    uses: VecDeque<Instant>,
+}
+
+impl Window {
+    /// Forgets the invocations that left the window.
+    fn expire(&mut self, now: Instant) {
+        while self.uses.front().is_some_and(|first| now.duration_since(*first) >= self.per) {
+            self.uses.pop_front();
+        }
+    }
+}
+
+/// Identifies a bucket: command name, bucket kind and bucket key.
+type BucketKey = (String, CooldownBucket, u64);
+
+/// Recent invocations of every bucket.
+static USES: Lazy<Mutex<HashMap<BucketKey, Window>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Records an invocation of `command`, or fails with the remaining wait time if its
+/// cooldown is exhausted.
+pub fn hit_cooldown(command: &str, cooldown: &Cooldown, invocation: &Invocation<'_>) -> CommandResult {
+    hit_cooldown_at(command, cooldown, invocation, Instant::now())
+}
+
+/// Records an invocation of `command` made at `now`. See `hit_cooldown`.
+fn hit_cooldown_at(command: &str, cooldown: &Cooldown, invocation: &Invocation<'_>, now: Instant) -> CommandResult {
+    let mut uses = USES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    if uses.len() > SWEEP_THRESHOLD {
+        uses.retain(|_, window| {
+            window.expire(now);
+            !window.uses.is_empty()
+        });
+    }
+
+    let window = uses
+        .entry((command.to_string(), cooldown.bucket, cooldown.key(invocation)))
+        .or_default();
+    window.per = cooldown.per;
+    window.expire(now);
+
+    if window.uses.len() >= cooldown.uses {
+        let Some(oldest) = window.uses.front() else {
+            return Err(CommandError::user(format!("`/{command}` is disabled.")));
+        };
+        let remaining = cooldown.per.saturating_sub(now.duration_since(*oldest));
+        return Err(CommandError::user(format!(
+            "Slow down! You can use `/{command}` again in {}.",
+            format_wait(remaining)
+        )));
+    }
+
+    window.uses.push_back(now);
+    Ok(())
+}
+
+/// Formats a wait time as `42s` or `3m 20s`, rounding up to the next second.
+fn format_wait(wait: Duration) -> String {
+    let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    if secs < 60 {
+        format!("{secs}s")
+    } else {
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::all::*;
+    use super::*;
+    use crate::invocation::InvocationSource;
+
+    /// Runs `test` with an invocation by the user `user_id` in a guild channel.
+    fn with_invocation(user_id: u64, test: impl FnOnce(&Invocation<'_>)) {
+        let mut message = Message::default();
+        message.author.id = UserId::new(user_id);
+        let invocation = Invocation {
+            command: "test",
+            user: &message.author,
+            guild_id: Some(GuildId::new(1)),
+            channel_id: ChannelId::new(2),
+            roles: &[],
+            permissions: None,
+            source: InvocationSource::Message(&message),
+        };
+        test(&invocation);
+    }
+
+    fn wait_error(result: CommandResult) -> String {
+        match result {
+            Err(CommandError::User(message)) => message,
+            other => panic!("expected a cooldown error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn a_burst_of_uses_is_allowed() {
+        let cooldown = Cooldown::new(CooldownBucket::User, 3, Duration::from_secs(10));
+        let now = Instant::now();
+        with_invocation(1, |invocation| {
+            for _ in 0..3 {
+                assert!(hit_cooldown_at("burst", &cooldown, invocation, now).is_ok());
+            }
+            let error = wait_error(hit_cooldown_at("burst", &cooldown, invocation, now));
+            assert_eq!(error, "Slow down! You can use `/burst` again in 10s.");
+        });
+    }
+
+    #[test]
+    fn uses_leave_the_window_one_by_one() {
+        let cooldown = Cooldown::new(CooldownBucket::User, 2, Duration::from_secs(10));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        with_invocation(1, |invocation| {
+            assert!(hit_cooldown_at("window", &cooldown, invocation, at(0)).is_ok());
+            assert!(hit_cooldown_at("window", &cooldown, invocation, at(4)).is_ok());
+            let error = wait_error(hit_cooldown_at("window", &cooldown, invocation, at(9)));
+            assert!(error.ends_with("again in 1s."), "{error}");
+
+            assert!(hit_cooldown_at("window", &cooldown, invocation, at(10)).is_ok());
+            let error = wait_error(hit_cooldown_at("window", &cooldown, invocation, at(10)));
+            assert!(error.ends_with("again in 4s."), "{error}");
+        });
+    }
+
+    #[test]
+    fn buckets_are_counted_separately() {
+        let cooldown = Cooldown::new(CooldownBucket::User, 1, Duration::from_secs(10));
+        let now = Instant::now();
+        with_invocation(1, |invocation| assert!(hit_cooldown_at("buckets", &cooldown, invocation, now).is_ok()));
+        with_invocation(2, |invocation| assert!(hit_cooldown_at("buckets", &cooldown, invocation, now).is_ok()));
+        with_invocation(1, |invocation| assert!(hit_cooldown_at("buckets", &cooldown, invocation, now).is_err()));
+        with_invocation(1, |invocation| assert!(hit_cooldown_at("other", &cooldown, invocation, now).is_ok()));
+    }
+
+    #[test]
+    fn zero_uses_disable_the_command() {
+        let cooldown = Cooldown::new(CooldownBucket::Global, 0, Duration::from_secs(10));
+        with_invocation(1, |invocation| {
+            let error = wait_error(hit_cooldown_at("never", &cooldown, invocation, Instant::now()));
+            assert_eq!(error, "`/never` is disabled.");
+        });
+    }
+
+    #[test]
+    fn wait_times_round_up_to_the_second() {
+        assert_eq!(format_wait(Duration::ZERO), "0s");
+        assert_eq!(format_wait(Duration::from_nanos(1)), "1s");
+        assert_eq!(format_wait(Duration::from_millis(1200)), "2s");
+        assert_eq!(format_wait(Duration::from_secs(59)), "59s");
+        assert_eq!(format_wait(Duration::from_millis(59_500)), "1m 0s");
+        assert_eq!(format_wait(Duration::from_secs(200)), "3m 20s");
+    }
+
+    #[test]
+    fn cooldowns_describe_themselves() {
+        assert_eq!(Cooldown::new(CooldownBucket::User, 1, Duration::from_secs(10)).to_string(), "1 use every 10s per user");
+        assert_eq!(Cooldown::new(CooldownBucket::Global, 3, Duration::from_secs(90)).to_string(), "3 uses every 1m 30s");
+    }
+}
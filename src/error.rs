@@ -0,0 +1,144 @@
+use std::fmt;
+
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::args::ArgError;
+use crate::component::ComponentIdError;
+use crate::modal::ModalFieldError;
+use crate::state::StateError;
+
+/// The error type returned by command, component and modal handlers.
+///
+/// The dispatcher turns every error into an ephemeral reply to the user and a log line.
+#[derive(Debug)]
+pub enum CommandError {
+    /// The user did something wrong (bad input, unknown item...). The message is shown as is.
+    User(String),
+    /// The user is not allowed to do this. The message is shown as is.
+    Permission(String),
+    /// Something failed on our side. The user gets a generic message, the details are logged.
+    Internal(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// The result type returned by command, component and modal handlers.
+pub type CommandResult = Result<(), CommandError>;
+
+impl CommandError {
+    /// Creates a `CommandError::User` with the given message.
+    pub fn user(message: impl Into<String>) -> Self {
+        CommandError::User(message.into())
+    }
+
+    /// Creates a `CommandError::Permission` with the given message.
+    pub fn permission(message: impl Into<String>) -> Self {
+        CommandError::Permission(message.into())
+    }
+
+    /// Creates a `CommandError::Internal` from any error.
+    pub fn internal(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        CommandError::Internal(err.into())
+    }
+
+    /// The message shown to the user for this error.
+    pub fn user_message(&self) -> String {
+        match self {
+            CommandError::User(message) => format!("❌ {message}"),
+            CommandError::Permission(message) => format!("🚫 {message}"),
+            CommandError::Internal(_) => "⚠️ Something went wrong while running this command.".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::User(message) => write!(f, "user error: {message}"),
+            CommandError::Permission(message) => write!(f, "permission error: {message}"),
+            CommandError::Internal(err) => write!(f, "internal error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<serenity::Error> for CommandError {
+    fn from(err: serenity::Error) -> Self {
+        CommandError::internal(err)
+    }
+}
+
+impl From<ArgError> for CommandError {
+    fn from(err: ArgError) -> Self {
+        CommandError::user(err.to_string())
+    }
+}
+
+impl From<ModalFieldError> for CommandError {
+    fn from(err: ModalFieldError) -> Self {
+        CommandError::user(err.to_string())
+    }
+}
+
+impl From<ComponentIdError> for CommandError {
+    fn from(err: ComponentIdError) -> Self {
+        CommandError::internal(err)
+    }
+}
+
+impl From<StateError> for CommandError {
+    fn from(err: StateError) -> Self {
+        CommandError::internal(err)
+    }
+}
+
+/// An interaction that can receive an ephemeral error reply.
+#[async_trait]
+pub trait ErrorReply: Sync {
+    /// Sends `content` as an ephemeral message, as the initial response if there was none yet
+    /// and as a follow-up otherwise.
+    async fn reply_ephemeral(&self, ctx: &Context, content: String) -> Result<(), serenity::Error>;
+
+    /// The user who triggered the interaction.
+    fn invoker(&self) -> &User;
+}
+
+macro_rules! impl_error_reply {
+    ($($interaction:ty),*) => {$(
+        #[async_trait]
+        impl ErrorReply for $interaction {
+            async fn reply_ephemeral(&self, ctx: &Context, content: String) -> Result<(), serenity::Error> {
+                let response = CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new().content(content.clone()).ephemeral(true),
+                );
+                if self.create_response(ctx, response).await.is_err() {
+                    self.create_followup(
+                        ctx,
+                        CreateInteractionResponseFollowup::new().content(content).ephemeral(true),
+                    ).await?;
+                }
+                Ok(())
+            }
+
+            fn invoker(&self) -> &User {
+                &self.user
+            }
+        }
+    )*};
+}
+
+impl_error_reply!(ComponentInteraction, ModalInteraction);
+
+/// Reports a failed handler: logs the error and tells the user what went wrong.
+///
+/// `origin` describes what was running, e.g. `/sum` or `component counter:increment:3`.
+pub async fn report_error(ctx: &Context, interaction: &impl ErrorReply, origin: &str, err: CommandError) {
+    let user = &interaction.invoker().name;
+    match &err {
+        CommandError::Internal(inner) => eprintln!("Error in {origin} (used by {user}): {inner:?}"),
+        _ => println!("{origin} (used by {user}) failed: {err}"),
+    }
+
+    if let Err(reply_err) = interaction.reply_ephemeral(ctx, err.user_message()).await {
+        eprintln!("Could not report error of {origin} to {user}: {reply_err:?}");
+    }
+}
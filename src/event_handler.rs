@@ -1,6 +1,19 @@
 use serenity::all::*;
 use async_trait::async_trait;
-use crate::command::all_slash_commands;
+use crate::command::dispatch_slash_command;
+use crate::component::dispatch_component;
+use crate::context_menu::dispatch_context_menu;
+use crate::error::report_error;
+use crate::invocation::Invocation;
+use crate::middleware::run_with_middlewares;
+use crate::modal::dispatch_modal;
+use crate::prefix::dispatch_prefix_command;
+use crate::registration::extension_kind;
+use crate::registry::registry;
+use crate::response::Responder;
+use crate::state::StateExt;
+use crate::style::EmbedStyle;
+use crate::subcommand::full_name;
 
 /// Trait for creating modular event handlers.
 ///
@@ -13,46 +26,18 @@ pub trait BotEventHandler: Sync + Send {
 
     /// Called when the bot is ready.
     async fn on_ready(&self, _ctx: &Context, _ready: &Ready) {}
-}
 
-/// Trait for types that have a static instance used for event registration.
-///
-/// Implement this for your struct and define a `const INSTANCE` to register it via the macro.
-pub trait HasInstance {
-    /// The static instance of your event handler.
-    const INSTANCE: Self;
+    /// Called when a guild becomes available, on startup or when the bot joins it.
+    async fn on_guild_create(&self, _ctx: &Context, _guild: &Guild, _is_new: Option<bool>) {}
 }
 
-/// Macro to register a bot event handler using `inventory`.
-///
-/// Use this macro at the end of your module to register your event handler automatically:
-///
-/// ```
-/// register_bot_event_handler!(MyEventHandler);
-/// ```
-#[macro_export]
-macro_rules! register_bot_event_handler {
-    ($handler:ty) => {
-        inventory::submit! {
-            &<$handler as $crate::event_handler::HasInstance>::INSTANCE
-                as &'static (dyn $crate::event_handler::BotEventHandler + Sync + Send)
-        }
-    };
-}
-
-/// Collect all registered bot event handlers.
-///
-/// This is used internally by the main event dispatcher to call all handlers.
-inventory::collect!(&'static (dyn BotEventHandler + Sync + Send));
-
-/// Returns all collected event handlers.
-pub fn all_event_handlers() -> Vec<&'static (dyn BotEventHandler + Sync + Send)> {
-    let mut handlers = Vec::new();
-    for handler in inventory::iter::<&'static (dyn BotEventHandler + Sync + Send)>() {
-        handlers.push(*handler);
-    }
-    handlers
-}
+// Collect all registered bot event handlers.
+//
+// This is used internally by the main event dispatcher to call all handlers.
+extension_kind!(
+    /// Returns all collected event handlers.
+    pub fn all_event_handlers() -> BotEventHandler
+);
 
 /// The main event handler for Serenity.
 ///
@@ -66,6 +51,7 @@ impl EventHandler for MainEventHandler {
         for handler in all_event_handlers() {
             handler.on_message(&ctx, &msg).await;
         }
+        dispatch_prefix_command(&ctx, &msg).await;
     }
 
     async fn ready(&self, ctx: Context, ready: Ready) {
@@ -74,13 +60,53 @@ impl EventHandler for MainEventHandler {
         }
     }
 
+    async fn guild_create(&self, ctx: Context, guild: Guild, is_new: Option<bool>) {
+        for handler in all_event_handlers() {
+            handler.on_guild_create(&ctx, &guild, is_new).await;
+        }
+    }
+
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::Command(command_interaction) = interaction {
-            for cmd in all_slash_commands() {
-                if cmd.name() == command_interaction.data.name {
-                    cmd.run(&ctx, &command_interaction).await;
+        match interaction {
+            Interaction::Command(command_interaction) => {
+                let name = full_name(&command_interaction.data.name, &command_interaction.data.options());
+                let invocation = Invocation::from_interaction(&name, &command_interaction);
+                let style = ctx.state::<EmbedStyle>().await.unwrap_or_default();
+                let response = Responder::new(&ctx, &command_interaction, style);
+                let command = async {
+                    if command_interaction.data.kind == CommandType::ChatInput {
+                        dispatch_slash_command(&ctx, &invocation, &command_interaction.data, &response).await
+                    } else {
+                        dispatch_context_menu(&ctx, &command_interaction, &response).await
+                    }
+                };
+
+                if let Err(err) = run_with_middlewares(&ctx, &invocation, command).await {
+                    let origin = match command_interaction.data.kind {
+                        CommandType::ChatInput => format!("/{name}"),
+                        _ => name.clone(),
+                    };
+                    report_error(&ctx, &response, &origin, err).await;
+                }
+            }
+            Interaction::Autocomplete(autocomplete_interaction) => {
+                if let Some(cmd) = registry().slash_command(&autocomplete_interaction.data.name) {
+                    cmd.dispatch_autocomplete(&ctx, &autocomplete_interaction).await;
+                }
+            }
+            Interaction::Component(component_interaction) => {
+                if let Err(err) = dispatch_component(&ctx, &component_interaction).await {
+                    let origin = format!("component {}", component_interaction.data.custom_id);
+                    report_error(&ctx, &component_interaction, &origin, err).await;
+                }
+            }
+            Interaction::Modal(modal_interaction) => {
+                if let Err(err) = dispatch_modal(&ctx, &modal_interaction).await {
+                    let origin = format!("modal {}", modal_interaction.data.custom_id);
+                    report_error(&ctx, &modal_interaction, &origin, err).await;
                 }
             }
+            _ => {}
         }
     }
 }
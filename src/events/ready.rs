@@ -1,8 +1,11 @@
 use serenity::all::*;
 use async_trait::async_trait;
-use crate::command::register_global_slash_commands;
-use crate::event_handler::{BotEventHandler, HasInstance};
-use crate::register_bot_event_handler;
+use crate::config::CONFIG;
+use crate::scope::{sync_global_commands, sync_guild_commands};
+use crate::sync::{CommandChange, SyncTarget};
+use crate::event_handler::BotEventHandler;
+use crate::registration::HasInstance;
+use crate::register;
 
 pub struct SlashReadyEvent;
 
@@ -14,13 +17,30 @@ impl HasInstance for SlashReadyEvent {
 impl BotEventHandler for SlashReadyEvent {
     async fn on_ready(&self, ctx: &Context, ready: &Ready) {
         println!("Bot ready as {}", ready.user.name);
+        if let Some(dev_guild) = CONFIG.dev_guild {
+            println!("Development mode: all commands are registered in guild {dev_guild}.");
+        }
+
+        report_sync(SyncTarget::Global, sync_global_commands(ctx).await);
+    }
+
+    async fn on_guild_create(&self, ctx: &Context, guild: &Guild, _is_new: Option<bool>) {
+        report_sync(SyncTarget::Guild(guild.id), sync_guild_commands(ctx, guild.id).await);
+    }
+}
 
-        if let Err(err) = register_global_slash_commands(ctx).await {
-            eprintln!("Error registering slash commands: {err:?}");
-        } else {
-            println!("Slash commands registered successfully.");
+/// Logs the outcome of a command sync.
+fn report_sync(target: SyncTarget, result: Result<Vec<CommandChange>, serenity::Error>) {
+    match result {
+        Ok(changes) if changes.is_empty() => {}
+        Ok(changes) => {
+            println!("Synced {target}:");
+            for change in changes {
+                println!("  {change}");
+            }
         }
+        Err(err) => eprintln!("Error syncing {target}: {err:?}"),
     }
 }
 
-register_bot_event_handler!(SlashReadyEvent);
\ No newline at end of file
+register!(SlashReadyEvent as BotEventHandler);
\ No newline at end of file
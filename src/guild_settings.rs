@@ -0,0 +1,348 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use serenity::all::*;
+use tokio::sync::Mutex;
+use crate::error::{CommandError, CommandResult};
+use crate::invocation::Invocation;
+use crate::state::StateExt;
+
+/// The settings server admins change at runtime for their guild.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GuildSettings {
+    /// The commands turned off in the guild, by registered name (`sum`, `User info`).
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub disabled_commands: BTreeSet<String>,
+
+    /// Who may use each command and where, by registered name. Commands without rules are
+    /// open to everyone their checks let through.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub access: BTreeMap<String, AccessRules>,
+
+    /// Whether the bot last registered guild commands in the guild, so that they get deleted
+    /// once it has none left (e.g. after `DEV_GUILD_ID` is unset). Not changed by admins.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub has_guild_commands: bool,
+}
+
+impl GuildSettings {
+    /// Whether the command named `name` may be used in the guild.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled_commands.contains(name)
+    }
+}
+
+/// The roles, users or channels a command is explicitly allowed for or denied to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessList<T: Ord> {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub allow: BTreeSet<T>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub deny: BTreeSet<T>,
+}
+
+impl<T: Ord> Default for AccessList<T> {
+    fn default() -> Self {
+        AccessList { allow: BTreeSet::new(), deny: BTreeSet::new() }
+    }
+}
+
+impl<T: Ord + Copy> AccessList<T> {
+    /// Puts `id` on the allow list or the deny list (taking it off the other one), or takes
+    /// it off both.
+    pub fn apply(&mut self, id: T, change: AccessChange) {
+        self.allow.remove(&id);
+        self.deny.remove(&id);
+        match change {
+            AccessChange::Allow => self.allow.insert(id),
+            AccessChange::Deny => self.deny.insert(id),
+            AccessChange::Clear => false,
+        };
+    }
+
+    /// Whether both lists are empty.
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+}
+
+/// A change to an `AccessList`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessChange {
+    Allow,
+    Deny,
+    Clear,
+}
+
+/// The access rules of one command in a guild, on top of its checks and Discord's permissions.
+///
+/// They are evaluated in this order:
+/// 1. a denied channel, or a channel missing from a non-empty allow list, blocks everyone;
+/// 2. a denied user is blocked, an allowed user is let through whatever their roles;
+/// 3. a user with a denied role is blocked, and so is a user with none of the allowed roles
+///    when that list isn't empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccessRules {
+    #[serde(default)]
+    pub roles: AccessList<RoleId>,
+    #[serde(default)]
+    pub users: AccessList<UserId>,
+    #[serde(default)]
+    pub channels: AccessList<ChannelId>,
+}
+
+impl AccessRules {
+    /// Whether there are no rules at all, i.e. the command is open.
+    pub fn is_empty(&self) -> bool {
+        self.roles.is_empty() && self.users.is_empty() && self.channels.is_empty()
+    }
+
+    /// Evaluates the rules for `invocation`, returning why it is denied.
+    pub fn check(&self, invocation: &Invocation<'_>) -> Result<(), String> {
+        self.check_member(invocation.user.id, invocation.channel_id, invocation.roles)
+    }
+
+    /// Evaluates the rules for `user`, holding `roles`, in `channel_id`. See `check`.
+    pub fn check_member(&self, user: UserId, channel_id: ChannelId, roles: &[RoleId]) -> Result<(), String> {
+        let channels = &self.channels;
+        if channels.deny.contains(&channel_id) {
+            return Err("This command can't be used in this channel.".to_string());
+        }
+        if !channels.allow.is_empty() && !channels.allow.contains(&channel_id) {
+            return Err(format!("This command can only be used in {}.", mentions(&channels.allow)));
+        }
+
+        if self.users.deny.contains(&user) {
+            return Err("You are not allowed to use this command.".to_string());
+        }
+        if self.users.allow.contains(&user) {
+            return Ok(());
+        }
+
+        if let Some(role) = roles.iter().find(|role| self.roles.deny.contains(role)) {
+            return Err(format!("Members with the {} role can't use this command.", role.mention()));
+        }
+        if !self.roles.allow.is_empty() && !roles.iter().any(|role| self.roles.allow.contains(role)) {
+            return Err(format!("This command needs one of these roles: {}.", mentions(&self.roles.allow)));
+        }
+        Ok(())
+    }
+}
+
+/// Lists `ids` as mentions, e.g. `<#1>, <#2>`.
+pub fn mentions<T: Mentionable>(ids: &BTreeSet<T>) -> String {
+    ids.iter().map(|id| id.mention().to_string()).collect::<Vec<_>>().join(", ")
+}
+
+/// The settings of every guild, saved to a JSON file after each change.
+///
+/// It is provided in the `AppState`, so handlers get it with `ctx.state::<GuildSettingsStore>()`.
+pub struct GuildSettingsStore {
+    path: PathBuf,
+    /// Only held briefly and never across I/O, so reads from the dispatcher never wait on the file.
+    guilds: RwLock<BTreeMap<GuildId, GuildSettings>>,
+    /// Held while saving, so that concurrent changes are written in order.
+    saving: Mutex<()>,
+}
+
+impl GuildSettingsStore {
+    /// Loads the settings saved in `path`, starting empty if the file doesn't exist yet.
+    ///
+    /// Panics if the file can't be read or parsed, rather than overwriting it on the next change.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let guilds = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .unwrap_or_else(|err| panic!("Invalid guild settings in {}: {err}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => panic!("Could not read the guild settings in {}: {err}", path.display()),
+        };
+        GuildSettingsStore { path, guilds: RwLock::new(guilds), saving: Mutex::new(()) }
+    }
+
+    /// Returns the settings of `guild_id`, the defaults if they were never changed.
+    pub fn get(&self, guild_id: GuildId) -> GuildSettings {
+        let guilds = self.guilds.read().unwrap_or_else(|err| err.into_inner());
+        guilds.get(&guild_id).cloned().unwrap_or_default()
+    }
+
+    /// Changes the settings of `guild_id` with `change` and saves every guild to the file.
+    ///
+    /// The change applies right away; if saving fails, it is lost on restart.
+    pub async fn update<T>(&self, guild_id: GuildId, change: impl FnOnce(&mut GuildSettings) -> T) -> io::Result<T> {
+        let _saving = self.saving.lock().await;
+        let (output, json) = {
+            let mut guilds = self.guilds.write().unwrap_or_else(|err| err.into_inner());
+            let settings = guilds.entry(guild_id).or_default();
+            let output = change(settings);
+            settings.access.retain(|_, rules| !rules.is_empty());
+            if *settings == GuildSettings::default() {
+                guilds.remove(&guild_id);
+            }
+            (output, serde_json::to_string_pretty(&*guilds).map_err(io::Error::other)?)
+        };
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || save(&path, json)).await.map_err(io::Error::other)??;
+        Ok(output)
+    }
+}
+
+/// Writes `json` to `path`, through a temporary file so a crash can't leave it half written.
+fn save(path: &Path, json: String) -> io::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_extension("json.tmp");
+    std::fs::write(&temp, json)?;
+    std::fs::rename(temp, path)
+}
+
+/// Returns the settings of `guild_id`, the defaults in DMs or if no store was provided.
+pub async fn guild_settings(ctx: &Context, guild_id: Option<GuildId>) -> GuildSettings {
+    match (guild_id, ctx.state::<GuildSettingsStore>().await) {
+        (Some(guild_id), Ok(store)) => store.get(guild_id),
+        _ => GuildSettings::default(),
+    }
+}
+
+/// Applies the settings of the guild to a component or modal belonging to `command`, as the
+/// middlewares do for the command itself: it is refused where the command is disabled, or
+/// denied to `user` by its access rules.
+pub async fn check_command_access(
+    ctx: &Context,
+    command: &str,
+    guild_id: Option<GuildId>,
+    channel_id: ChannelId,
+    user: &User,
+    member: Option<&Member>,
+) -> CommandResult {
+    let Some(guild_id) = guild_id else {
+        return Ok(());
+    };
+    let settings = guild_settings(ctx, Some(guild_id)).await;
+    if !settings.is_enabled(command) {
+        return Err(CommandError::user("This command is disabled in this server."));
+    }
+    let roles = member.map_or(&[][..], |member| member.roles.as_slice());
+    if let Some(rules) = settings.access.get(command)
+        && let Err(reason) = rules.check_member(user.id, channel_id, roles)
+    {
+        println!("Denied a {command} button or modal to {} in {channel_id} of guild {guild_id}: {reason}", user.name);
+        return Err(CommandError::permission(reason));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invocation::InvocationSource;
+
+    const USER: UserId = UserId::new(10);
+    const CHANNEL: ChannelId = ChannelId::new(20);
+    const OTHER_CHANNEL: ChannelId = ChannelId::new(21);
+    const ROLE: RoleId = RoleId::new(30);
+    const OTHER_ROLE: RoleId = RoleId::new(31);
+
+    /// Checks `rules` for `USER`, holding `roles`, in `CHANNEL`.
+    fn check(rules: &AccessRules, roles: &[RoleId]) -> Result<(), String> {
+        let mut message = Message::default();
+        message.author.id = USER;
+        let invocation = Invocation {
+            command: "sum",
+            user: &message.author,
+            guild_id: Some(GuildId::new(1)),
+            channel_id: CHANNEL,
+            roles,
+            permissions: None,
+            source: InvocationSource::Message(&message),
+        };
+        rules.check(&invocation)
+    }
+
+    fn rules(change: impl FnOnce(&mut AccessRules)) -> AccessRules {
+        let mut rules = AccessRules::default();
+        change(&mut rules);
+        rules
+    }
+
+    #[test]
+    fn no_rules_let_everyone_through() {
+        assert_eq!(check(&AccessRules::default(), &[]), Ok(()));
+        assert_eq!(check(&AccessRules::default(), &[ROLE]), Ok(()));
+    }
+
+    #[test]
+    fn channel_rules_block_everyone() {
+        let denied = rules(|rules| rules.channels.apply(CHANNEL, AccessChange::Deny));
+        assert_eq!(check(&denied, &[]), Err("This command can't be used in this channel.".to_string()));
+
+        let elsewhere = rules(|rules| rules.channels.apply(OTHER_CHANNEL, AccessChange::Allow));
+        assert_eq!(check(&elsewhere, &[]), Err("This command can only be used in <#21>.".to_string()));
+
+        let here = rules(|rules| rules.channels.apply(CHANNEL, AccessChange::Allow));
+        assert_eq!(check(&here, &[]), Ok(()));
+    }
+
+    #[test]
+    fn channel_rules_come_before_allowed_users() {
+        let rules = rules(|rules| {
+            rules.channels.apply(CHANNEL, AccessChange::Deny);
+            rules.users.apply(USER, AccessChange::Allow);
+        });
+        assert_eq!(check(&rules, &[]), Err("This command can't be used in this channel.".to_string()));
+    }
+
+    #[test]
+    fn user_rules_are_checked() {
+        let denied = rules(|rules| rules.users.apply(USER, AccessChange::Deny));
+        assert_eq!(check(&denied, &[]), Err("You are not allowed to use this command.".to_string()));
+
+        let allowed = rules(|rules| rules.users.apply(USER, AccessChange::Allow));
+        assert_eq!(check(&allowed, &[]), Ok(()));
+    }
+
+    #[test]
+    fn role_rules_are_checked() {
+        let denied = rules(|rules| rules.roles.apply(ROLE, AccessChange::Deny));
+        assert_eq!(check(&denied, &[OTHER_ROLE, ROLE]), Err("Members with the <@&30> role can't use this command.".to_string()));
+        assert_eq!(check(&denied, &[OTHER_ROLE]), Ok(()));
+
+        let allowed = rules(|rules| rules.roles.apply(ROLE, AccessChange::Allow));
+        assert_eq!(check(&allowed, &[OTHER_ROLE, ROLE]), Ok(()));
+        assert_eq!(check(&allowed, &[OTHER_ROLE]), Err("This command needs one of these roles: <@&30>.".to_string()));
+        assert_eq!(check(&allowed, &[]), Err("This command needs one of these roles: <@&30>.".to_string()));
+    }
+
+    #[test]
+    fn allowed_users_bypass_role_rules() {
+        let rules = rules(|rules| {
+            rules.roles.apply(ROLE, AccessChange::Deny);
+            rules.roles.apply(OTHER_ROLE, AccessChange::Allow);
+            rules.users.apply(USER, AccessChange::Allow);
+        });
+        assert_eq!(check(&rules, &[ROLE]), Ok(()));
+    }
+
+    #[test]
+    fn denied_users_are_blocked_whatever_their_roles() {
+        let rules = rules(|rules| {
+            rules.roles.apply(ROLE, AccessChange::Allow);
+            rules.users.apply(USER, AccessChange::Deny);
+        });
+        assert_eq!(check(&rules, &[ROLE]), Err("You are not allowed to use this command.".to_string()));
+    }
+
+    #[test]
+    fn applying_a_change_moves_the_id_between_lists() {
+        let mut list = AccessList::default();
+        list.apply(ROLE, AccessChange::Allow);
+        list.apply(ROLE, AccessChange::Deny);
+        assert!(list.allow.is_empty() && list.deny.contains(&ROLE));
+        list.apply(ROLE, AccessChange::Clear);
+        assert!(list.is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use serenity::all::*;
+use crate::config::CONFIG;
+
+/// The locale used when a text has no translation in the user's locale.
+pub const FALLBACK_LOCALE: &str = "en-US";
+
+/// The locales Discord supports, which are also the names of the translation files.
+const DISCORD_LOCALES: &[&str] = &[
+    "id", "da", "de", "en-GB", "en-US", "es-ES", "es-419", "fr", "hr", "it", "lt", "hu", "nl", "no",
+    "pl", "pt-BR", "ro", "fi", "sv-SE", "vi", "tr", "cs", "el", "bg", "ru", "uk", "hi", "th", "zh-CN",
+    "ja", "zh-TW", "ko",
+];
+
+/// The translations of every locale: locale -> key -> text.
+type Translations = HashMap<String, HashMap<String, String>>;
+
+/// The translation files shipped with the bot, so replies never fall back to raw keys
+/// when `LOCALES_DIR` is missing.
+const BUNDLED_LOCALES: &[(&str, &str)] = &[
+    ("en-US", include_str!("../locales/en-US.json")),
+    ("fr", include_str!("../locales/fr.json")),
+    ("de", include_str!("../locales/de.json")),
+];
+
+/// The bundled translations, overridden by the files of `LOCALES_DIR` on first use.
+static TRANSLATIONS: Lazy<Translations> = Lazy::new(|| load(&CONFIG.locales_dir));
+
+/// Loads the bundled translations, then every `{locale}.json` file of `dir` on top of them.
+///
+/// Nested objects are flattened into dotted keys, so `{"ping": {"pong": "Pong!"}}` defines
+/// `ping.pong`. A file only replaces the keys it defines. Files that aren't named after a
+/// Discord locale or can't be parsed are skipped.
+fn load(dir: &str) -> Translations {
+    let mut translations = Translations::new();
+    for (locale, text) in BUNDLED_LOCALES {
+        let json = serde_json::from_str(text).unwrap_or_else(|err| panic!("Invalid bundled translations for {locale}: {err}"));
+        flatten("", &json, translations.entry(locale.to_string()).or_default());
+    }
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("Could not read the translations in {dir} ({err}), using the bundled ones");
+            return translations;
+        }
+    };
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+        let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if !DISCORD_LOCALES.contains(&locale) {
+            eprintln!("Skipping {}: `{locale}` is not a Discord locale", path.display());
+            continue;
+        }
+        let json = match fs::read_to_string(&path).map_err(|err| err.to_string()).and_then(|text| {
+            serde_json::from_str::<Value>(&text).map_err(|err| err.to_string())
+        }) {
+            Ok(json) => json,
+            Err(err) => {
+                eprintln!("Skipping {}: {err}", path.display());
+                continue;
+            }
+        };
+        flatten("", &json, translations.entry(locale.to_string()).or_default());
+    }
+    translations
+}
+
+fn flatten(prefix: &str, json: &Value, texts: &mut HashMap<String, String>) {
+    match json {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                flatten(&key, value, texts);
+            }
+        }
+        Value::String(text) => {
+            texts.insert(prefix.to_string(), text.clone());
+        }
+        _ => eprintln!("Ignoring translation `{prefix}`: translations must be strings"),
+    }
+}
+
+/// The translation of `key` in `locale`, if there is one.
+pub fn lookup(locale: &str, key: &str) -> Option<&'static str> {
+    TRANSLATIONS.get(locale)?.get(key).map(String::as_str)
+}
+
+/// Translates `key` for `locale`, falling back to `FALLBACK_LOCALE` and then to the key itself.
+///
+/// `{name}` placeholders are replaced with the matching value of `args`:
+/// ```
+/// translate("fr", "sum.result", &[("result", &12)]) // "Résultat : 12"
+/// ```
+pub fn translate(locale: &str, key: &str, args: &[(&str, &(dyn Display + Sync))]) -> String {
+    let mut text = lookup(locale, key)
+        .or_else(|| lookup(FALLBACK_LOCALE, key))
+        .unwrap_or(key)
+        .to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    text
+}
+
+/// Every translation of `key`, as `(locale, text)` pairs.
+fn translations_of(key: &str) -> Vec<(&'static str, &'static str)> {
+    TRANSLATIONS
+        .iter()
+        .filter_map(|(locale, texts)| Some((locale.as_str(), texts.get(key)?.as_str())))
+        .collect()
+}
+
+thread_local! {
+    /// The names of the commands and subcommands whose options are being built.
+    static SCOPE: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `build` with `name` appended to the path used by `localize_option`.
+///
+/// Command builders wrap the construction of their options with this, so that options,
+/// which don't know which command they belong to, find their translations.
+pub fn in_scope<T>(name: &str, build: impl FnOnce() -> T) -> T {
+    SCOPE.with(|scope| scope.borrow_mut().push(name.to_string()));
+    let result = build();
+    SCOPE.with(|scope| scope.borrow_mut().pop());
+    result
+}
+
+/// The translation key prefix of `name` in the current scope, e.g. `commands.sum.a`.
+fn key_of(name: &str) -> String {
+    SCOPE.with(|scope| {
+        let mut key = String::from("commands");
+        for parent in scope.borrow().iter() {
+            key.push('.');
+            key.push_str(parent);
+        }
+        key.push('.');
+        key.push_str(name);
+        key
+    })
+}
+
+/// Adds the translations of the name and description of the slash command `name`
+/// (`commands.{name}.name` and `commands.{name}.description`).
+pub fn localize_command(mut command: CreateCommand, name: &str) -> CreateCommand {
+    let key = key_of(name);
+    for (locale, text) in translations_of(&format!("{key}.name")) {
+        command = command.name_localized(locale, text);
+    }
+    for (locale, text) in translations_of(&format!("{key}.description")) {
+        command = command.description_localized(locale, text);
+    }
+    command
+}
+
+/// Adds the translations of the name of the context-menu command `name` (`commands.{name}.name`).
+pub fn localize_menu(mut command: CreateCommand, name: &str) -> CreateCommand {
+    for (locale, text) in translations_of(&format!("{}.name", key_of(name))) {
+        command = command.name_localized(locale, text);
+    }
+    command
+}
+
+/// Adds the translations of the name and description of the option `name`, which is
+/// looked up under the command being built, e.g. `commands.sum.a.description`.
+pub fn localize_option(mut option: CreateCommandOption, name: &str) -> CreateCommandOption {
+    let key = key_of(name);
+    for (locale, text) in translations_of(&format!("{key}.name")) {
+        option = option.name_localized(locale, text);
+    }
+    for (locale, text) in translations_of(&format!("{key}.description")) {
+        option = option.description_localized(locale, text);
+    }
+    option
+}
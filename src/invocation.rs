@@ -0,0 +1,72 @@
+use serenity::all::*;
+
+/// How a command was invoked.
+#[derive(Debug, Clone, Copy)]
+pub enum InvocationSource<'a> {
+    /// Through an interaction: a slash or context-menu command.
+    Interaction(&'a CommandInteraction),
+    /// Through a message starting with the command prefix, see `prefix`.
+    Message(&'a Message),
+}
+
+/// Who used a command, and where.
+///
+/// This is the part of an interaction that checks, cooldowns and middlewares look at,
+/// independent of how the command was invoked.
+#[derive(Debug, Clone, Copy)]
+pub struct Invocation<'a> {
+    /// The full name of the command, e.g. `config set` for a subcommand.
+    pub command: &'a str,
+    /// The user who used the command.
+    pub user: &'a User,
+    /// The guild the command was used in, `None` in DMs.
+    pub guild_id: Option<GuildId>,
+    /// The channel the command was used in.
+    pub channel_id: ChannelId,
+    /// The roles of the user in the guild (empty in DMs).
+    pub roles: &'a [RoleId],
+    /// The permissions of the user in the channel, if known.
+    pub permissions: Option<Permissions>,
+    /// The interaction or message the command was invoked with.
+    pub source: InvocationSource<'a>,
+}
+
+impl<'a> Invocation<'a> {
+    /// Describes the use of `command` through a command interaction.
+    pub fn from_interaction(command: &'a str, interaction: &'a CommandInteraction) -> Self {
+        let member = interaction.member.as_deref();
+        Invocation {
+            command,
+            user: &interaction.user,
+            guild_id: interaction.guild_id,
+            channel_id: interaction.channel_id,
+            roles: member.map_or(&[], |m| m.roles.as_slice()),
+            permissions: member.and_then(|m| m.permissions),
+            source: InvocationSource::Interaction(interaction),
+        }
+    }
+
+    /// Describes the use of `command` through a prefix command message.
+    ///
+    /// Messages don't carry the permissions of their author, so they are computed by the
+    /// caller (see `prefix::author_permissions`).
+    pub fn from_message(command: &'a str, message: &'a Message, permissions: Option<Permissions>) -> Self {
+        Invocation {
+            command,
+            user: &message.author,
+            guild_id: message.guild_id,
+            channel_id: message.channel_id,
+            roles: message.member.as_deref().map_or(&[], |m| m.roles.as_slice()),
+            permissions,
+            source: InvocationSource::Message(message),
+        }
+    }
+
+    /// The registered name of the command, without its subcommands: `config` for `config set`.
+    pub fn root_command(&self) -> &'a str {
+        match self.source {
+            InvocationSource::Interaction(interaction) => &interaction.data.name,
+            InvocationSource::Message(_) => self.command.split(' ').next().unwrap_or(self.command),
+        }
+    }
+}
@@ -1,23 +1,59 @@
-#[macro_use]
-extern crate inventory;
-
+mod args;
+mod checks;
 mod command;
 mod commands;
+mod component;
+mod config;
+mod context_menu;
+mod cooldown;
+mod error;
 mod event_handler;
 mod events;
+mod guild_settings;
+mod i18n;
+mod invocation;
+mod middleware;
+mod middlewares;
+mod modal;
+mod prefix;
+mod registration;
+mod registry;
+mod response;
+mod scope;
+mod state;
+mod style;
+mod subcommand;
+mod sync;
+mod validate;
+
+use std::sync::Arc;
 
+use config::CONFIG;
 use event_handler::MainEventHandler;
+use guild_settings::GuildSettingsStore;
+use middlewares::stats::BotStats;
 use serenity::all::*;
 use dotenv::dotenv;
+use state::AppState;
+use style::EmbedStyle;
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
+    registry::init_registry();
 
     let token = std::env::var("DISCORD_TOKEN").expect("Missing DISCORD_TOKEN env variable");
 
+    // Shared values that commands and handlers get with `ctx.state::<T>()`
+    let state = AppState::builder()
+        .with(BotStats::new())
+        .with(EmbedStyle::new().colour(Colour::BLURPLE).footer("Discord-Bot"))
+        .with(GuildSettingsStore::load(&CONFIG.guild_settings_file))
+        .build();
+
     let mut client = Client::builder(token, GatewayIntents::all())
         .event_handler(MainEventHandler)
+        .type_map_insert::<AppState>(Arc::new(state))
         .await
         .expect("Error creating client");
 
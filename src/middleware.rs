@@ -0,0 +1,83 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::error::CommandResult;
+use crate::invocation::Invocation;
+use crate::registration::extension_kind;
+
+/// A trait for code that wraps every command invocation (slash and context-menu commands).
+///
+/// Middlewares run in order of `priority()`: the `before` hooks first, then the command
+/// (checks, cooldown and handler), then the `after` hooks in reverse order. Typical uses are
+/// logging, timing, metrics or blocking commands during maintenance.
+///
+/// Use `register!(MyMiddleware as Middleware)` to automatically register the middleware
+/// via the inventory system.
+#[async_trait]
+pub trait Middleware: Sync + Send {
+    /// (Optional) The position of this middleware in the chain: lower values run their
+    /// `before` hook earlier and their `after` hook later. Default is `0`.
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// (Optional) Called before the command runs.
+    ///
+    /// Returning an error short-circuits the invocation: the command and the remaining
+    /// middlewares are skipped, and the error is reported to the user like a command error.
+    async fn before(&self, _ctx: &Context, _invocation: &Invocation<'_>) -> CommandResult {
+        Ok(())
+    }
+
+    /// (Optional) Called after the command ran, or after a later middleware short-circuited it.
+    ///
+    /// # Arguments
+    /// * `result` - The outcome of the invocation.
+    /// * `elapsed` - The time spent since the first `before` hook.
+    async fn after(&self, _ctx: &Context, _invocation: &Invocation<'_>, _result: &CommandResult, _elapsed: Duration) {}
+}
+
+extension_kind!(
+    fn registered_middlewares() -> Middleware
+);
+
+/// Returns all middlewares registered in the inventory, ordered by priority.
+pub fn all_middlewares() -> Vec<&'static (dyn Middleware + Sync + Send)> {
+    let mut middlewares = registered_middlewares();
+    middlewares.sort_by_key(|middleware| middleware.priority());
+    middlewares
+}
+
+/// Runs `command` inside the middleware chain.
+///
+/// Only the middlewares whose `before` hook succeeded get their `after` hook called.
+pub async fn run_with_middlewares(
+    ctx: &Context,
+    invocation: &Invocation<'_>,
+    command: impl Future<Output = CommandResult>,
+) -> CommandResult {
+    let middlewares = all_middlewares();
+    let start = Instant::now();
+
+    let mut entered = 0;
+    let mut short_circuit = None;
+    for middleware in &middlewares {
+        if let Err(err) = middleware.before(ctx, invocation).await {
+            short_circuit = Some(err);
+            break;
+        }
+        entered += 1;
+    }
+
+    let result = match short_circuit {
+        Some(err) => Err(err),
+        None => command.await,
+    };
+
+    for middleware in middlewares[..entered].iter().rev() {
+        middleware.after(ctx, invocation, &result, start.elapsed()).await;
+    }
+    result
+}
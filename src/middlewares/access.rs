@@ -0,0 +1,38 @@
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::registration::HasInstance;
+use crate::error::{CommandError, CommandResult};
+use crate::guild_settings::GuildSettingsStore;
+use crate::invocation::Invocation;
+use crate::middleware::Middleware;
+use crate::state::StateExt;
+use crate::register;
+
+/// Enforces the access rules a guild set with `/commands access`, logging every denial.
+pub struct AccessMiddleware;
+
+impl HasInstance for AccessMiddleware {
+    const INSTANCE: Self = AccessMiddleware;
+}
+
+#[async_trait]
+impl Middleware for AccessMiddleware {
+    async fn before(&self, ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+        let Some(guild_id) = invocation.guild_id else {
+            return Ok(());
+        };
+        let settings = ctx.state::<GuildSettingsStore>().await?.get(guild_id);
+        if let Some(rules) = settings.access.get(invocation.root_command())
+            && let Err(reason) = rules.check(invocation)
+        {
+            println!(
+                "Denied {} to {} in {} of guild {guild_id}: {reason}",
+                invocation.command, invocation.user.name, invocation.channel_id
+            );
+            return Err(CommandError::permission(reason));
+        }
+        Ok(())
+    }
+}
+
+register!(AccessMiddleware as Middleware);
@@ -0,0 +1,32 @@
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::registration::HasInstance;
+use crate::error::{CommandError, CommandResult};
+use crate::guild_settings::GuildSettingsStore;
+use crate::invocation::Invocation;
+use crate::middleware::Middleware;
+use crate::state::StateExt;
+use crate::register;
+
+/// Blocks the commands a guild turned off with `/commands disable`.
+pub struct DisabledCommandsMiddleware;
+
+impl HasInstance for DisabledCommandsMiddleware {
+    const INSTANCE: Self = DisabledCommandsMiddleware;
+}
+
+#[async_trait]
+impl Middleware for DisabledCommandsMiddleware {
+    async fn before(&self, ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+        let Some(guild_id) = invocation.guild_id else {
+            return Ok(());
+        };
+        let settings = ctx.state::<GuildSettingsStore>().await?.get(guild_id);
+        if !settings.is_enabled(invocation.root_command()) {
+            return Err(CommandError::user("This command is disabled in this server."));
+        }
+        Ok(())
+    }
+}
+
+register!(DisabledCommandsMiddleware as Middleware);
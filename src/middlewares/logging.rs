@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::registration::HasInstance;
+use crate::error::CommandResult;
+use crate::invocation::Invocation;
+use crate::middleware::Middleware;
+use crate::register;
+
+/// Logs every command invocation with its outcome and duration.
+pub struct LoggingMiddleware;
+
+impl HasInstance for LoggingMiddleware {
+    const INSTANCE: Self = LoggingMiddleware;
+}
+
+#[async_trait]
+impl Middleware for LoggingMiddleware {
+    // Outermost, so the timing covers the other middlewares too.
+    fn priority(&self) -> i32 { -100 }
+
+    async fn after(&self, _ctx: &Context, invocation: &Invocation<'_>, result: &CommandResult, elapsed: Duration) {
+        let outcome = if result.is_ok() { "ok" } else { "failed" };
+        println!(
+            "{} used {} in {} ({outcome}, {}ms)",
+            invocation.user.name,
+            invocation.command,
+            invocation.channel_id,
+            elapsed.as_millis()
+        );
+    }
+}
+
+register!(LoggingMiddleware as Middleware);
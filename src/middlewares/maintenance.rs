@@ -0,0 +1,27 @@
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::registration::HasInstance;
+use crate::config::CONFIG;
+use crate::error::{CommandError, CommandResult};
+use crate::invocation::Invocation;
+use crate::middleware::Middleware;
+use crate::register;
+
+/// Blocks every command except for the owners while `MAINTENANCE_MODE` is set.
+pub struct MaintenanceMiddleware;
+
+impl HasInstance for MaintenanceMiddleware {
+    const INSTANCE: Self = MaintenanceMiddleware;
+}
+
+#[async_trait]
+impl Middleware for MaintenanceMiddleware {
+    async fn before(&self, _ctx: &Context, invocation: &Invocation<'_>) -> CommandResult {
+        if CONFIG.maintenance && !CONFIG.owners.contains(&invocation.user.id) {
+            return Err(CommandError::user("The bot is under maintenance, please try again later."));
+        }
+        Ok(())
+    }
+}
+
+register!(MaintenanceMiddleware as Middleware);
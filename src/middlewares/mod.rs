@@ -0,0 +1,5 @@
+mod access;
+mod disabled_commands;
+mod logging;
+mod maintenance;
+pub mod stats;
@@ -0,0 +1,52 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::registration::HasInstance;
+use crate::error::CommandResult;
+use crate::invocation::Invocation;
+use crate::middleware::Middleware;
+use crate::state::StateExt;
+use crate::register;
+
+/// Usage statistics of the bot, shared through the application state.
+pub struct BotStats {
+    /// When the bot started. Read by `/stats`, in the `utility` module.
+    #[cfg_attr(not(feature = "utility"), allow(dead_code))]
+    pub started: Instant,
+    /// How many commands ran successfully.
+    pub succeeded: AtomicU64,
+    /// How many commands failed or were blocked.
+    pub failed: AtomicU64,
+}
+
+impl BotStats {
+    pub fn new() -> Self {
+        BotStats {
+            started: Instant::now(),
+            succeeded: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Counts command outcomes in `BotStats`.
+pub struct StatsMiddleware;
+
+impl HasInstance for StatsMiddleware {
+    const INSTANCE: Self = StatsMiddleware;
+}
+
+#[async_trait]
+impl Middleware for StatsMiddleware {
+    async fn after(&self, ctx: &Context, _invocation: &Invocation<'_>, result: &CommandResult, _elapsed: Duration) {
+        let Ok(stats) = ctx.state::<BotStats>().await else {
+            return;
+        };
+        let counter = if result.is_ok() { &stats.succeeded } else { &stats.failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+register!(StatsMiddleware as Middleware);
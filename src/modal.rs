@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::commands;
+use crate::component::ComponentId;
+use crate::error::{CommandError, CommandResult};
+use crate::guild_settings::check_command_access;
+use crate::response::Responder;
+use crate::registration::extension_kind;
+
+/// A trait that handles submitted modals (forms).
+///
+/// Modals are routed like message components: their `custom_id` follows the
+/// `namespace:action:arg1...` convention, and every handler owns one namespace.
+///
+/// Use `register!(MyHandler as ModalHandler)` to automatically register the handler
+/// via the inventory system.
+#[async_trait]
+pub trait ModalHandler: Sync + Send {
+    /// The namespace routed to this handler, i.e. the first segment of the modal `custom_id`.
+    fn namespace(&self) -> &'static str;
+
+    /// (Optional) The category of the command this handler belongs to, see `SlashCommand::category`.
+    ///
+    /// Handlers of a module disabled with `DISABLED_MODULES` are not run. Default is `"General"`.
+    fn category(&self) -> &'static str {
+        "General"
+    }
+
+    /// (Optional) The registered name of the command this handler belongs to, e.g. `"counter"`.
+    ///
+    /// When set, the handler is refused in guilds that turned that command off or whose
+    /// access rules deny it to the user, like the command itself. Default is `None`.
+    fn command(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The logic to be executed when a modal of this namespace is submitted.
+    ///
+    /// # Arguments
+    /// * `ctx` - The bot context provided by Serenity.
+    /// * `interaction` - The modal submit interaction.
+    /// * `id` - The parsed `custom_id` of the modal.
+    /// * `fields` - The submitted text inputs, keyed by their `custom_id`.
+    ///
+    /// Returning `ModalFieldError`s with `?` reports them to the user.
+    async fn handle(&self, ctx: &Context, interaction: &ModalInteraction, id: ComponentId<'_>, fields: ModalFields) -> CommandResult;
+}
+
+/// The text inputs submitted with a modal, keyed by their `custom_id`.
+#[derive(Debug, Clone, Default)]
+pub struct ModalFields {
+    values: HashMap<String, String>,
+}
+
+impl ModalFields {
+    /// Collects the text inputs of a submitted modal.
+    pub fn from_interaction(interaction: &ModalInteraction) -> Self {
+        let values = interaction
+            .data
+            .components
+            .iter()
+            .flat_map(|row| &row.components)
+            .filter_map(|component| match component {
+                ActionRowComponent::InputText(input) => {
+                    Some((input.custom_id.clone(), input.value.clone().unwrap_or_default()))
+                }
+                _ => None,
+            })
+            .collect();
+        ModalFields { values }
+    }
+
+    /// Returns the value of a field, or `None` if it was left empty.
+    pub fn optional(&self, custom_id: &str) -> Option<&str> {
+        self.values
+            .get(custom_id)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Returns the value of a field that must not be empty.
+    pub fn text(&self, custom_id: &str) -> Result<&str, ModalFieldError> {
+        self.optional(custom_id)
+            .ok_or_else(|| ModalFieldError::Missing(custom_id.to_string()))
+    }
+
+    /// Parses the value of a field that must not be empty as a `T`.
+    pub fn parse<T: FromStr>(&self, custom_id: &str) -> Result<T, ModalFieldError> {
+        let value = self.text(custom_id)?;
+        value.parse().map_err(|_| ModalFieldError::Invalid {
+            field: custom_id.to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    /// Parses the value of a field as a `T`, or returns `None` if it was left empty.
+    pub fn parse_optional<T: FromStr>(&self, custom_id: &str) -> Result<Option<T>, ModalFieldError> {
+        match self.optional(custom_id) {
+            Some(_) => self.parse(custom_id).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// An error raised while reading the fields of a submitted modal.
+#[derive(Debug, Clone)]
+pub enum ModalFieldError {
+    /// A required field was left empty.
+    Missing(String),
+    /// A field could not be parsed into the expected type.
+    Invalid { field: String, value: String },
+}
+
+impl fmt::Display for ModalFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModalFieldError::Missing(field) => write!(f, "The field `{field}` is required."),
+            ModalFieldError::Invalid { field, value } => write!(f, "`{value}` is not a valid value for `{field}`."),
+        }
+    }
+}
+
+impl std::error::Error for ModalFieldError {}
+
+/// A builder for multi-field modals.
+///
+/// Every text input is placed on its own row, as required by Discord (up to five).
+/// Modifiers such as `optional` or `placeholder` apply to the last added input.
+///
+/// ```
+/// ModalBuilder::new("feedback:submit", "Send feedback")
+///     .short("topic", "Topic")
+///     .paragraph("details", "What happened?")
+///     .placeholder("Tell us everything")
+///     .short("rating", "Rating (1-5)")
+///     .optional();
+/// ```
+// Only used by `/feedback`, in the `utility` module.
+#[cfg_attr(not(feature = "utility"), allow(dead_code))]
+pub struct ModalBuilder {
+    custom_id: String,
+    title: String,
+    inputs: Vec<CreateInputText>,
+}
+
+#[cfg_attr(not(feature = "utility"), allow(dead_code))]
+impl ModalBuilder {
+    /// Starts a modal with the given `custom_id` and title.
+    pub fn new(custom_id: impl Into<String>, title: impl Into<String>) -> Self {
+        ModalBuilder {
+            custom_id: custom_id.into(),
+            title: title.into(),
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Adds a single-line, required text input.
+    pub fn short(self, custom_id: &str, label: &str) -> Self {
+        self.input(CreateInputText::new(InputTextStyle::Short, label, custom_id))
+    }
+
+    /// Adds a multi-line, required text input.
+    pub fn paragraph(self, custom_id: &str, label: &str) -> Self {
+        self.input(CreateInputText::new(InputTextStyle::Paragraph, label, custom_id))
+    }
+
+    /// Adds a fully customised text input.
+    pub fn input(mut self, input: CreateInputText) -> Self {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Makes the last added input optional.
+    pub fn optional(self) -> Self {
+        self.map_last(|input| input.required(false))
+    }
+
+    /// Sets the placeholder of the last added input.
+    pub fn placeholder(self, placeholder: &str) -> Self {
+        self.map_last(|input| input.placeholder(placeholder))
+    }
+
+    /// Sets the maximum length of the last added input.
+    pub fn max_length(self, max: u16) -> Self {
+        self.map_last(|input| input.max_length(max))
+    }
+
+    fn map_last(mut self, f: impl FnOnce(CreateInputText) -> CreateInputText) -> Self {
+        if let Some(input) = self.inputs.pop() {
+            self.inputs.push(f(input));
+        }
+        self
+    }
+
+    /// Builds the `CreateModal`.
+    pub fn build(self) -> CreateModal {
+        CreateModal::new(self.custom_id, self.title).components(
+            self.inputs
+                .into_iter()
+                .map(CreateActionRow::InputText)
+                .collect(),
+        )
+    }
+}
+
+/// Presents a modal in response to a slash command.
+///
+/// This must be the first response to the interaction.
+#[cfg_attr(not(feature = "utility"), allow(dead_code))]
+pub async fn show_modal(response: &Responder<'_>, modal: ModalBuilder) -> Result<(), serenity::Error> {
+    response.modal(modal.build()).await
+}
+
+extension_kind!(
+    /// Returns a list of all modal handlers registered in the inventory.
+    pub fn all_modal_handlers() -> ModalHandler
+);
+
+/// Routes a modal submit interaction to the handler owning its namespace.
+pub async fn dispatch_modal(ctx: &Context, interaction: &ModalInteraction) -> CommandResult {
+    let id = ComponentId::parse(&interaction.data.custom_id);
+    match all_modal_handlers().into_iter().find(|h| h.namespace() == id.namespace) {
+        Some(handler) if !commands::is_enabled(handler.category()) => {
+            Err(CommandError::user("This feature is disabled."))
+        }
+        Some(handler) => {
+            if let Some(command) = handler.command() {
+                check_command_access(ctx, command, interaction.guild_id, interaction.channel_id, &interaction.user, interaction.member.as_ref()).await?;
+            }
+            let fields = ModalFields::from_interaction(interaction);
+            handler.handle(ctx, interaction, id, fields).await
+        }
+        None => Err(CommandError::internal(format!(
+            "no modal handler for custom_id `{}`",
+            interaction.data.custom_id
+        ))),
+    }
+}
@@ -0,0 +1,545 @@
+// Prefix commands: slash commands used through a message such as `!sum 1 2` or `@Bot sum 1 2`.
+//
+// The text after the command name is parsed into the options the command registers, as
+// `CommandData` (the data of a slash command interaction). The command then goes through the
+// same dispatcher, checks, cooldowns and middlewares as its slash form, and replies in the
+// channel through `Responder::from_message`.
+
+use serde_json::{json, Map, Value};
+use serenity::all::*;
+use crate::args::kind_name;
+use crate::command::{dispatch_slash_command, DynSlashCommand};
+use crate::config::CONFIG;
+use crate::error::{report_error, CommandError};
+use crate::invocation::Invocation;
+use crate::middleware::run_with_middlewares;
+use crate::registry::registry;
+use crate::response::Responder;
+use crate::state::StateExt;
+use crate::style::EmbedStyle;
+use crate::subcommand::full_name;
+
+/// The largest integer Discord accepts for `Integer` options (2^53 - 1), in either direction.
+const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
+/// The words of a prefix command, split on whitespace. `"Quoted text"` counts as one word.
+struct Words<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Words<'a> {
+    fn new(text: &'a str) -> Self {
+        Words { rest: text }
+    }
+
+    /// The next word, without its quotes.
+    fn next(&mut self) -> Option<&'a str> {
+        let text = self.rest.trim_start();
+        if text.is_empty() {
+            self.rest = text;
+            return None;
+        }
+        if let Some(quoted) = text.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            self.rest = quoted.get(end + 1..).unwrap_or("");
+            return Some(&quoted[..end]);
+        }
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        self.rest = &text[end..];
+        Some(&text[..end])
+    }
+
+    /// All the remaining text, for the last text option, without the quotes if it is a single quoted string.
+    fn rest(&mut self) -> Option<&'a str> {
+        let text = self.rest.trim();
+        self.rest = "";
+        match text
+            .strip_prefix('"')
+            .and_then(|quoted| quoted.strip_suffix('"'))
+            .filter(|unquoted| !unquoted.contains('"'))
+        {
+            Some(unquoted) => Some(unquoted),
+            None => Some(text).filter(|text| !text.is_empty()),
+        }
+    }
+}
+
+/// The text of `content` after the command prefix or the bot mention, if it starts with one.
+fn strip_prefix<'a>(ctx: &Context, content: &'a str) -> Option<&'a str> {
+    if let Some(prefix) = &CONFIG.prefix
+        && let Some(rest) = content.strip_prefix(prefix.as_str())
+    {
+        return Some(rest);
+    }
+    if CONFIG.mention_prefix {
+        let id = ctx.cache.current_user().id;
+        for mention in [format!("<@{id}>"), format!("<@!{id}>")] {
+            if let Some(rest) = content.strip_prefix(&mention) {
+                return Some(rest);
+            }
+        }
+    }
+    None
+}
+
+/// The permissions of the author of `message` in its channel, if the guild is cached.
+pub fn author_permissions(ctx: &Context, message: &Message) -> Option<Permissions> {
+    let guild = message.guild(&ctx.cache)?;
+    let channel = guild.channels.get(&message.channel_id)?;
+    let member = message.member.as_deref()?;
+    Some(guild.partial_member_permissions_in(channel, message.author.id, member))
+}
+
+/// Parses an ID written as a mention (`<@123>`, `<#123>`...) or as a plain number.
+fn parse_id(word: &str, mention_prefixes: &[&str]) -> Option<u64> {
+    let id = mention_prefixes
+        .iter()
+        .find_map(|prefix| word.strip_prefix(prefix)?.strip_suffix('>'))
+        .unwrap_or(word);
+    id.parse().ok().filter(|id| *id != 0)
+}
+
+/// Builds the `CommandData` of a prefix command from the data that `command` registers.
+struct Parser<'a> {
+    ctx: &'a Context,
+    message: &'a Message,
+    attachments: std::slice::Iter<'a, Attachment>,
+    resolved: Map<String, Value>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(ctx: &'a Context, message: &'a Message) -> Self {
+        Parser {
+            ctx,
+            message,
+            attachments: message.attachments.iter(),
+            resolved: Map::new(),
+        }
+    }
+
+    /// Records an entity in the `resolved` data, e.g. `users`.
+    fn resolve(&mut self, kind: &str, id: u64, entity: Value) {
+        let entities = self.resolved.entry(kind).or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(entities) = entities {
+            entities.insert(id.to_string(), entity);
+        }
+    }
+
+    /// Parses the options of `command` out of `words`.
+    async fn parse(mut self, command: &dyn DynSlashCommand, words: &mut Words<'_>) -> Result<CommandData, CommandError> {
+        let registered = serde_json::to_value(command.register()).map_err(CommandError::internal)?;
+        let name = command.name();
+
+        let options = registered.get("options").and_then(Value::as_array).cloned().unwrap_or_default();
+        let (path, options) = walk_subcommands(name, options, words)?;
+
+        let mut values = self.parse_options(&options, words).await?;
+        if words.next().is_some() {
+            return Err(CommandError::user("Too many options. Put text containing spaces in \"quotes\"."));
+        }
+        for (name, kind) in path.into_iter().rev() {
+            values = vec![json!({ "name": name, "type": kind, "options": values })];
+        }
+
+        let data = json!({
+            // Prefix commands have no interaction: the message ID stands in for the command ID.
+            "id": self.message.id.to_string(),
+            "name": name,
+            "type": 1,
+            "options": values,
+            "resolved": self.resolved,
+            "guild_id": self.message.guild_id,
+        });
+        serde_json::from_value(data).map_err(CommandError::internal)
+    }
+
+    /// Parses the values of the leaf `options` in order. Attachments are taken from the message,
+    /// and the last text option takes the rest of the message.
+    async fn parse_options(&mut self, options: &[Value], words: &mut Words<'_>) -> Result<Vec<Value>, CommandError> {
+        let last_word_option = options.iter().rposition(|o| option_type(o) != CommandOptionType::Attachment);
+        let mut values = Vec::new();
+        for (index, option) in options.iter().enumerate() {
+            let name = option.get("name").and_then(Value::as_str).unwrap_or_default();
+            let kind = option_type(option);
+
+            let value = if kind == CommandOptionType::Attachment {
+                let Some(attachment) = self.attachments.next() else {
+                    continue;
+                };
+                self.resolve("attachments", attachment.id.get(), serde_json::to_value(attachment).map_err(CommandError::internal)?);
+                json!(attachment.id)
+            } else {
+                let word = if kind == CommandOptionType::String && Some(index) == last_word_option {
+                    words.rest()
+                } else {
+                    words.next()
+                };
+                let Some(word) = word else {
+                    // Missing required options are reported when the arguments are parsed.
+                    continue;
+                };
+                self.parse_value(option, name, kind, word).await?
+            };
+            values.push(json!({ "name": name, "type": u8::from(kind), "value": value }));
+        }
+        Ok(values)
+    }
+
+    /// Converts `word` into the value of `option`, looking up users, roles and channels.
+    async fn parse_value(&mut self, option: &Value, name: &str, kind: CommandOptionType, word: &str) -> Result<Value, CommandError> {
+        if let Some(value) = parse_literal(option, name, kind, word)? {
+            return Ok(value);
+        }
+
+        let invalid = || CommandError::user(format!("`{word}` is not {} (option `{name}`).", kind_name(kind)));
+        let value = match kind {
+            CommandOptionType::User => {
+                let id = parse_id(word, &["<@!", "<@"]).ok_or_else(invalid)?;
+                let user = match self.message.mentions.iter().find(|user| user.id.get() == id) {
+                    Some(user) => user.clone(),
+                    None => UserId::new(id).to_user(self.ctx).await.map_err(|_| invalid())?,
+                };
+                self.resolve("users", id, serde_json::to_value(user).map_err(CommandError::internal)?);
+                json!(id.to_string())
+            }
+            CommandOptionType::Role => {
+                let id = parse_id(word, &["<@&"]).ok_or_else(invalid)?;
+                let role = self
+                    .message
+                    .guild(&self.ctx.cache)
+                    .and_then(|guild| guild.roles.get(&RoleId::new(id)).cloned())
+                    .ok_or_else(invalid)?;
+                self.resolve("roles", id, serde_json::to_value(role).map_err(CommandError::internal)?);
+                json!(id.to_string())
+            }
+            CommandOptionType::Channel => {
+                let id = parse_id(word, &["<#"]).ok_or_else(invalid)?;
+                let channel = self
+                    .message
+                    .guild(&self.ctx.cache)
+                    .and_then(|guild| guild.channels.get(&ChannelId::new(id)).cloned())
+                    .ok_or_else(invalid)?;
+                let partial = json!({ "id": channel.id, "name": channel.name, "type": u8::from(channel.kind) });
+                self.resolve("channels", id, partial);
+                json!(id.to_string())
+            }
+            CommandOptionType::Mentionable => {
+                json!(parse_id(word, &["<@!", "<@&", "<@"]).ok_or_else(invalid)?.to_string())
+            }
+            _ => return Err(invalid()),
+        };
+        Ok(value)
+    }
+}
+
+/// The subcommand groups and subcommands named by a prefix command: their names and option types.
+type SubcommandPath = Vec<(String, Value)>;
+
+/// Walks down the subcommand groups and subcommands of the command `name` named by the first
+/// `words`, returning the path taken and the options reached.
+fn walk_subcommands(name: &str, mut options: Vec<Value>, words: &mut Words<'_>) -> Result<(SubcommandPath, Vec<Value>), CommandError> {
+    let mut path = Vec::new();
+    while options.iter().any(is_subcommand) {
+        let names = options.iter().filter_map(|o| o.get("name")?.as_str()).collect::<Vec<_>>().join("`, `");
+        let Some(word) = words.next() else {
+            return Err(CommandError::user(format!("`{name}` needs a subcommand: `{names}`.")));
+        };
+        let Some(option) = options.iter().find(|o| o.get("name").and_then(Value::as_str) == Some(word)) else {
+            return Err(CommandError::user(format!("`{word}` is not a subcommand of `{name}`: use `{names}`.")));
+        };
+        path.push((word.to_string(), option.get("type").cloned().unwrap_or_default()));
+        options = option.get("options").and_then(Value::as_array).cloned().unwrap_or_default();
+    }
+    Ok((path, options))
+}
+
+/// Converts `word` into the value of `option` if it is a choice, text, a number or a boolean,
+/// checking the limits of the option. Returns `None` for the other kinds of options.
+fn parse_literal(option: &Value, name: &str, kind: CommandOptionType, word: &str) -> Result<Option<Value>, CommandError> {
+    let invalid = || CommandError::user(format!("`{word}` is not {} (option `{name}`).", kind_name(kind)));
+
+    if let Some(choices) = option.get("choices").and_then(Value::as_array).filter(|choices| !choices.is_empty()) {
+        let choice = choices.iter().find(|choice| {
+            choice.get("name").and_then(Value::as_str).is_some_and(|n| n.eq_ignore_ascii_case(word))
+                || choice.get("value").is_some_and(|value| match value {
+                    Value::String(value) => value == word,
+                    value => value.as_f64().is_some() && value.as_f64() == word.parse().ok(),
+                })
+        });
+        return match choice.and_then(|choice| choice.get("value")) {
+            Some(value) => Ok(Some(value.clone())),
+            None => {
+                let names = choices.iter().filter_map(|c| c.get("name")?.as_str()).collect::<Vec<_>>().join("`, `");
+                Err(CommandError::user(format!("`{word}` is not a choice of option `{name}`: use `{names}`.")))
+            }
+        };
+    }
+
+    let value = match kind {
+        CommandOptionType::String => {
+            let length = word.chars().count() as u64;
+            if let Some(min) = option.get("min_length").and_then(Value::as_u64).filter(|min| length < *min) {
+                return Err(CommandError::user(format!("Option `{name}` must be at least {min} characters long.")));
+            }
+            if let Some(max) = option.get("max_length").and_then(Value::as_u64).filter(|max| length > *max) {
+                return Err(CommandError::user(format!("Option `{name}` must be at most {max} characters long.")));
+            }
+            json!(word)
+        }
+        CommandOptionType::Integer | CommandOptionType::Number => {
+            let (value, number) = if kind == CommandOptionType::Integer {
+                let integer = word.parse::<i64>().map_err(|_| invalid())?;
+                if !(-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&integer) {
+                    return Err(CommandError::user(format!(
+                        "Option `{name}` must be between -{MAX_SAFE_INTEGER} and {MAX_SAFE_INTEGER}."
+                    )));
+                }
+                (json!(integer), integer as f64)
+            } else {
+                let number = word.parse::<f64>().ok().filter(|n| n.is_finite()).ok_or_else(invalid)?;
+                (json!(number), number)
+            };
+            if let Some(min) = option.get("min_value").and_then(Value::as_f64).filter(|min| number < *min) {
+                return Err(CommandError::user(format!("Option `{name}` must be at least {min}.")));
+            }
+            if let Some(max) = option.get("max_value").and_then(Value::as_f64).filter(|max| number > *max) {
+                return Err(CommandError::user(format!("Option `{name}` must be at most {max}.")));
+            }
+            value
+        }
+        CommandOptionType::Boolean => match word.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => json!(true),
+            "false" | "no" | "off" | "0" => json!(false),
+            _ => return Err(invalid()),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+fn option_type(option: &Value) -> CommandOptionType {
+    let kind = option.get("type").and_then(Value::as_u64).unwrap_or_default();
+    CommandOptionType::from(u8::try_from(kind).unwrap_or_default())
+}
+
+fn is_subcommand(option: &Value) -> bool {
+    matches!(option_type(option), CommandOptionType::SubCommand | CommandOptionType::SubCommandGroup)
+}
+
+/// Runs the prefix command in `message`, if it starts with the prefix and names a slash command.
+///
+/// Messages from bots and messages naming no command are ignored.
+pub async fn dispatch_prefix_command(ctx: &Context, message: &Message) {
+    if message.author.bot {
+        return;
+    }
+    let Some(text) = strip_prefix(ctx, &message.content) else {
+        return;
+    };
+    let mut words = Words::new(text);
+    let Some(name) = words.next().map(str::to_lowercase) else {
+        return;
+    };
+    let Some(command) = registry().slash_command(&name) else {
+        return;
+    };
+    if !command.scope().available_in(message.guild_id) {
+        return;
+    }
+
+    let style = ctx.state::<EmbedStyle>().await.unwrap_or_default();
+    let response = Responder::from_message(ctx, message, style);
+    let origin = format!("prefix command {name}");
+    if !command.prefix() {
+        let err = CommandError::user(format!("`{name}` only works as a slash command: use `/{name}`."));
+        report_error(ctx, &response, &origin, err).await;
+        return;
+    }
+
+    let data = match Parser::new(ctx, message).parse(command, &mut words).await {
+        Ok(data) => data,
+        Err(err) => {
+            report_error(ctx, &response, &origin, err).await;
+            return;
+        }
+    };
+    let full_name = full_name(&data.name, &data.options());
+    let invocation = Invocation::from_message(&full_name, message, author_permissions(ctx, message));
+    let command = dispatch_slash_command(ctx, &invocation, &data, &response);
+    if let Err(err) = run_with_middlewares(ctx, &invocation, command).await {
+        report_error(ctx, &response, &origin, err).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option_json(option: CreateCommandOption) -> Value {
+        serde_json::to_value(option).unwrap()
+    }
+
+    fn literal(option: CreateCommandOption, word: &str) -> Result<Option<Value>, String> {
+        let option = option_json(option);
+        let name = option["name"].as_str().unwrap().to_string();
+        parse_literal(&option, &name, option_type(&option), word).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn words_split_on_whitespace() {
+        let mut words = Words::new("  sum 1\t2 ");
+        assert_eq!(words.next(), Some("sum"));
+        assert_eq!(words.next(), Some("1"));
+        assert_eq!(words.next(), Some("2"));
+        assert_eq!(words.next(), None);
+    }
+
+    #[test]
+    fn words_keep_quoted_text_together() {
+        let mut words = Words::new(r#""hello world" next "unterminated quote"#);
+        assert_eq!(words.next(), Some("hello world"));
+        assert_eq!(words.next(), Some("next"));
+        assert_eq!(words.next(), Some("unterminated quote"));
+        assert_eq!(words.next(), None);
+    }
+
+    #[test]
+    fn words_rest_takes_the_remaining_text() {
+        let mut words = Words::new("echo  the rest,  as typed ");
+        assert_eq!(words.next(), Some("echo"));
+        assert_eq!(words.rest(), Some("the rest,  as typed"));
+        assert_eq!(words.next(), None);
+        assert_eq!(words.rest(), None);
+
+        assert_eq!(Words::new(r#" "all quoted" "#).rest(), Some("all quoted"));
+        assert_eq!(Words::new(r#""a" and "b""#).rest(), Some(r#""a" and "b""#));
+        assert_eq!(Words::new("   ").rest(), None);
+    }
+
+    #[test]
+    fn ids_are_read_from_mentions_or_numbers() {
+        assert_eq!(parse_id("<@42>", &["<@!", "<@"]), Some(42));
+        assert_eq!(parse_id("<@!42>", &["<@!", "<@"]), Some(42));
+        assert_eq!(parse_id("<#42>", &["<#"]), Some(42));
+        assert_eq!(parse_id("42", &["<#"]), Some(42));
+        assert_eq!(parse_id("<@42>", &["<#"]), None);
+        assert_eq!(parse_id("0", &["<#"]), None);
+        assert_eq!(parse_id("general", &["<#"]), None);
+    }
+
+    fn math_options() -> Vec<Value> {
+        let number = |name| CreateCommandOption::new(CommandOptionType::Number, name, "A number").required(true);
+        let command = CreateCommand::new("math")
+            .description("Small calculations")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "multiply", "Multiplies")
+                    .add_sub_option(number("a"))
+                    .add_sub_option(number("b")),
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommandGroup, "convert", "Converts")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::SubCommand, "celsius", "From Celsius").add_sub_option(number("value"))),
+            );
+        serde_json::to_value(command).unwrap()["options"].as_array().cloned().unwrap()
+    }
+
+    #[test]
+    fn subcommands_are_walked_down_to_their_options() {
+        let mut words = Words::new("convert celsius 12");
+        let (path, options) = walk_subcommands("math", math_options(), &mut words).unwrap();
+        assert_eq!(path, vec![("convert".to_string(), json!(2)), ("celsius".to_string(), json!(1))]);
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0]["name"], "value");
+        assert_eq!(words.next(), Some("12"));
+    }
+
+    #[test]
+    fn subcommands_must_be_named() {
+        let err = walk_subcommands("math", math_options(), &mut Words::new("")).unwrap_err();
+        assert!(err.to_string().contains("needs a subcommand: `multiply`, `convert`"), "{err}");
+
+        let err = walk_subcommands("math", math_options(), &mut Words::new("divide 1 2")).unwrap_err();
+        assert!(err.to_string().contains("`divide` is not a subcommand of `math`"), "{err}");
+
+        let err = walk_subcommands("math", math_options(), &mut Words::new("convert")).unwrap_err();
+        assert!(err.to_string().contains("needs a subcommand: `celsius`"), "{err}");
+    }
+
+    #[test]
+    fn flat_commands_have_no_subcommand_path() {
+        let options = vec![option_json(CreateCommandOption::new(CommandOptionType::Integer, "a", "A"))];
+        let mut words = Words::new("1");
+        let (path, reached) = walk_subcommands("sum", options.clone(), &mut words).unwrap();
+        assert!(path.is_empty());
+        assert_eq!(reached, options);
+        assert_eq!(words.next(), Some("1"));
+    }
+
+    #[test]
+    fn choices_match_names_and_values() {
+        let unit = CreateCommandOption::new(CommandOptionType::Integer, "unit", "Unit")
+            .add_int_choice("Seconds", 1)
+            .add_int_choice("Minutes", 60);
+        assert_eq!(literal(unit.clone(), "minutes"), Ok(Some(json!(60))));
+        assert_eq!(literal(unit.clone(), "1"), Ok(Some(json!(1))));
+        let err = literal(unit, "hours").unwrap_err();
+        assert!(err.contains("not a choice of option `unit`: use `Seconds`, `Minutes`"), "{err}");
+
+        let colour = CreateCommandOption::new(CommandOptionType::String, "colour", "Colour").add_string_choice("Red", "red");
+        assert_eq!(literal(colour.clone(), "red"), Ok(Some(json!("red"))));
+        assert_eq!(literal(colour.clone(), "RED"), Ok(Some(json!("red"))));
+        assert!(literal(colour, "blue").is_err());
+    }
+
+    #[test]
+    fn text_length_limits_are_checked() {
+        let code = CreateCommandOption::new(CommandOptionType::String, "code", "Code").min_length(2).max_length(3);
+        assert_eq!(literal(code.clone(), "ab"), Ok(Some(json!("ab"))));
+        assert_eq!(literal(code.clone(), "éèà"), Ok(Some(json!("éèà"))));
+        assert!(literal(code.clone(), "a").unwrap_err().contains("at least 2 characters"));
+        assert!(literal(code, "abcd").unwrap_err().contains("at most 3 characters"));
+    }
+
+    #[test]
+    fn integers_stay_within_discords_range() {
+        let a = || CreateCommandOption::new(CommandOptionType::Integer, "a", "A");
+        assert_eq!(literal(a(), "-12"), Ok(Some(json!(-12))));
+        assert_eq!(literal(a(), "9007199254740991"), Ok(Some(json!(MAX_SAFE_INTEGER))));
+        assert_eq!(literal(a(), "-9007199254740991"), Ok(Some(json!(-MAX_SAFE_INTEGER))));
+        assert!(literal(a(), "9007199254740992").unwrap_err().contains("must be between"));
+        assert!(literal(a(), "9223372036854775807").unwrap_err().contains("must be between"));
+        assert!(literal(a(), "1.5").unwrap_err().contains("is not"));
+        assert!(literal(a(), "ten").unwrap_err().contains("is not"));
+    }
+
+    #[test]
+    fn number_limits_are_checked() {
+        let count = CreateCommandOption::new(CommandOptionType::Integer, "count", "Count").min_int_value(1).max_int_value(100);
+        assert_eq!(literal(count.clone(), "100"), Ok(Some(json!(100))));
+        assert!(literal(count.clone(), "0").unwrap_err().contains("at least 1"));
+        assert!(literal(count, "101").unwrap_err().contains("at most 100"));
+
+        let value = || CreateCommandOption::new(CommandOptionType::Number, "value", "Value");
+        assert_eq!(literal(value(), "1.5"), Ok(Some(json!(1.5))));
+        assert!(literal(value(), "inf").is_err());
+        assert!(literal(value(), "NaN").is_err());
+    }
+
+    #[test]
+    fn booleans_accept_common_words() {
+        let flag = || CreateCommandOption::new(CommandOptionType::Boolean, "private", "Private");
+        for word in ["true", "Yes", "on", "1"] {
+            assert_eq!(literal(flag(), word), Ok(Some(json!(true))), "{word}");
+        }
+        for word in ["false", "NO", "off", "0"] {
+            assert_eq!(literal(flag(), word), Ok(Some(json!(false))), "{word}");
+        }
+        assert!(literal(flag(), "maybe").is_err());
+    }
+
+    #[test]
+    fn entities_are_left_to_the_parser() {
+        let user = CreateCommandOption::new(CommandOptionType::User, "user", "User");
+        assert_eq!(literal(user, "<@42>"), Ok(None));
+    }
+}
@@ -0,0 +1,81 @@
+// The registration mechanism shared by every kind of extension: slash and context-menu
+// commands, component and modal handlers, middlewares and event handlers.
+//
+// An extension is a struct with a static instance (`HasInstance`) that implements the
+// trait of its kind. It is registered with `register!` and collected through `inventory`.
+
+/// A helper trait to provide a static instance of an extension (command, handler...).
+///
+/// Implement this for your struct and define a `const INSTANCE` to register it with `register!`.
+pub trait HasInstance {
+    /// The static instance of your extension.
+    const INSTANCE: Self;
+}
+
+/// Macro to register a struct that implements `HasInstance` and the trait of its kind.
+///
+/// Usage:
+/// ```
+/// register!(PingCommand as SlashCommand);
+/// register!(MessageLogger as BotEventHandler);
+/// ```
+///
+/// The kinds are `SlashCommand`, `UserCommand`, `MessageCommand`, `ComponentHandler`,
+/// `ModalHandler`, `Middleware` and `BotEventHandler`. A new kind is declared with
+/// `extension_kind!` in its module and gets an arm here.
+#[macro_export]
+macro_rules! register {
+    (@submit $extension:ty, $object:path) => {
+        inventory::submit! {
+            &< $extension as $crate::registration::HasInstance >::INSTANCE
+                as &'static (dyn $object + Sync + Send)
+        }
+    };
+    ($extension:ty as SlashCommand) => {
+        $crate::register!(@submit $extension, $crate::command::DynSlashCommand);
+    };
+    ($extension:ty as UserCommand) => {
+        $crate::register!(@submit $extension, $crate::context_menu::UserCommand);
+    };
+    ($extension:ty as MessageCommand) => {
+        $crate::register!(@submit $extension, $crate::context_menu::MessageCommand);
+    };
+    ($extension:ty as ComponentHandler) => {
+        $crate::register!(@submit $extension, $crate::component::ComponentHandler);
+    };
+    ($extension:ty as ModalHandler) => {
+        $crate::register!(@submit $extension, $crate::modal::ModalHandler);
+    };
+    ($extension:ty as Middleware) => {
+        $crate::register!(@submit $extension, $crate::middleware::Middleware);
+    };
+    ($extension:ty as BotEventHandler) => {
+        $crate::register!(@submit $extension, $crate::event_handler::BotEventHandler);
+    };
+}
+
+/// Declares a kind of extension: collects its registrations from `inventory` and defines
+/// a function returning all of them.
+///
+/// Usage:
+/// ```
+/// extension_kind!(
+///     /// Returns all middlewares registered in the inventory.
+///     pub fn all_middlewares() -> Middleware
+/// );
+/// ```
+macro_rules! extension_kind {
+    ($(#[$doc:meta])* $vis:vis fn $all:ident() -> $object:path) => {
+        inventory::collect!(&'static (dyn $object + Sync + Send));
+
+        $(#[$doc])*
+        $vis fn $all() -> Vec<&'static (dyn $object + Sync + Send)> {
+            inventory::iter::<&'static (dyn $object + Sync + Send)>
+                .into_iter()
+                .copied()
+                .collect()
+        }
+    };
+}
+
+pub(crate) use extension_kind;
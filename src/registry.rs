@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use once_cell::sync::Lazy;
+use crate::command::{all_slash_commands, DynSlashCommand};
+use crate::commands;
+use crate::context_menu::{all_message_commands, all_user_commands, MessageCommand, UserCommand};
+use crate::validate::{validate_command, ValidationError};
+
+/// Every registered command, indexed by name.
+///
+/// The registry is built once from the inventory, on first use or when `init_registry`
+/// is called at startup, and every interaction is then routed with a single lookup.
+pub struct CommandRegistry {
+    slash_commands: HashMap<&'static str, &'static (dyn DynSlashCommand + Sync + Send)>,
+    user_commands: HashMap<&'static str, &'static (dyn UserCommand + Sync + Send)>,
+    message_commands: HashMap<&'static str, &'static (dyn MessageCommand + Sync + Send)>,
+}
+
+/// A problem found while building the command registry.
+#[derive(Debug, Clone)]
+pub enum RegistryError {
+    /// Two commands of the same kind share a name.
+    Duplicate { kind: &'static str, name: &'static str },
+    /// A command breaks Discord's limits.
+    Invalid(ValidationError),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Duplicate { kind, name } => write!(f, "{kind} `{name}` is registered more than once"),
+            RegistryError::Invalid(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+impl CommandRegistry {
+    /// Builds the registry from the inventory, collecting every duplicate name and every
+    /// command that breaks Discord's limits.
+    ///
+    /// Commands of the modules disabled with `DISABLED_MODULES` are left out, so they are
+    /// neither registered on Discord nor dispatched.
+    pub fn build() -> Result<Self, Vec<RegistryError>> {
+        let mut errors = Vec::new();
+        let slash_commands = enabled(all_slash_commands(), |cmd| cmd.category());
+        let user_commands = enabled(all_user_commands(), |cmd| cmd.category());
+        let message_commands = enabled(all_message_commands(), |cmd| cmd.category());
+        let registry = CommandRegistry {
+            slash_commands: index("slash command", slash_commands, |cmd| cmd.name(), &mut errors),
+            user_commands: index("user command", user_commands, |cmd| cmd.name(), &mut errors),
+            message_commands: index("message command", message_commands, |cmd| cmd.name(), &mut errors),
+        };
+
+        let commands = registry.slash_commands.values().map(|cmd| cmd.register())
+            .chain(registry.user_commands.values().map(|cmd| cmd.register()))
+            .chain(registry.message_commands.values().map(|cmd| cmd.register()));
+        for command in commands {
+            errors.extend(validate_command(&command).into_iter().map(RegistryError::Invalid));
+        }
+        errors.extend(registry.slash_commands.values().filter_map(|cmd| cmd.declaration_error()).map(RegistryError::Invalid));
+
+        if errors.is_empty() { Ok(registry) } else { Err(errors) }
+    }
+
+    /// Returns every slash command, sorted by name.
+    pub fn slash_commands(&self) -> Vec<&'static (dyn DynSlashCommand + Sync + Send)> {
+        let mut commands: Vec<_> = self.slash_commands.values().copied().collect();
+        commands.sort_by_key(|cmd| cmd.name());
+        commands
+    }
+
+    /// Returns every user context-menu command, sorted by name.
+    pub fn user_commands(&self) -> Vec<&'static (dyn UserCommand + Sync + Send)> {
+        let mut commands: Vec<_> = self.user_commands.values().copied().collect();
+        commands.sort_by_key(|cmd| cmd.name());
+        commands
+    }
+
+    /// Returns every message context-menu command, sorted by name.
+    pub fn message_commands(&self) -> Vec<&'static (dyn MessageCommand + Sync + Send)> {
+        let mut commands: Vec<_> = self.message_commands.values().copied().collect();
+        commands.sort_by_key(|cmd| cmd.name());
+        commands
+    }
+
+    /// Returns the slash command with the given name.
+    pub fn slash_command(&self, name: &str) -> Option<&'static (dyn DynSlashCommand + Sync + Send)> {
+        self.slash_commands.get(name).copied()
+    }
+
+    /// Returns the user context-menu command with the given name.
+    pub fn user_command(&self, name: &str) -> Option<&'static (dyn UserCommand + Sync + Send)> {
+        self.user_commands.get(name).copied()
+    }
+
+    /// Returns the message context-menu command with the given name.
+    pub fn message_command(&self, name: &str) -> Option<&'static (dyn MessageCommand + Sync + Send)> {
+        self.message_commands.get(name).copied()
+    }
+}
+
+/// Keeps the `commands` whose category is an enabled module, see `commands::is_enabled`.
+fn enabled<C>(commands: Vec<C>, category: impl Fn(&C) -> &'static str) -> Vec<C> {
+    commands.into_iter().filter(|command| commands::is_enabled(category(command))).collect()
+}
+
+/// Indexes `commands` by name, recording duplicate names in `errors`.
+fn index<C: Copy>(
+    kind: &'static str,
+    commands: Vec<C>,
+    name: impl Fn(&C) -> &'static str,
+    errors: &mut Vec<RegistryError>,
+) -> HashMap<&'static str, C> {
+    let mut index = HashMap::with_capacity(commands.len());
+    for command in commands {
+        let name = name(&command);
+        if index.insert(name, command).is_some() {
+            errors.push(RegistryError::Duplicate { kind, name });
+        }
+    }
+    index
+}
+
+static REGISTRY: Lazy<CommandRegistry> = Lazy::new(|| {
+    CommandRegistry::build().unwrap_or_else(|errors| {
+        let problems: Vec<String> = errors.iter().map(|err| format!("  - {err}")).collect();
+        panic!("Invalid command registry:\n{}", problems.join("\n"))
+    })
+});
+
+/// Returns the command registry.
+pub fn registry() -> &'static CommandRegistry {
+    &REGISTRY
+}
+
+/// Builds the command registry, panicking with every problem found if it is invalid.
+///
+/// Call this at startup so that mistakes fail fast instead of on the first interaction.
+pub fn init_registry() {
+    for name in commands::unknown_disabled_modules() {
+        eprintln!("DISABLED_MODULES: `{name}` is not a module of this build (modules: {})", commands::MODULES.join(", "));
+    }
+    Lazy::force(&REGISTRY);
+    println!("Enabled command modules: {}", commands::enabled_modules().join(", "));
+}
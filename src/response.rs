@@ -0,0 +1,463 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::all::*;
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use crate::error::{CommandResult, ErrorReply};
+use crate::i18n::{translate, FALLBACK_LOCALE};
+use crate::style::EmbedStyle;
+
+/// How long the dispatcher waits before deferring a command that hasn't responded yet.
+///
+/// Discord rejects initial responses sent more than three seconds after the interaction.
+pub const AUTO_DEFER_DELAY: Duration = Duration::from_millis(2500);
+
+/// When the response to a command is deferred (Discord shows "Bot is thinking...").
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Defer {
+    /// Never defer: the command always responds within three seconds.
+    Never,
+    /// Defer if the command hasn't responded after `AUTO_DEFER_DELAY`. This is the default.
+    AfterDelay { ephemeral: bool },
+    /// Defer before the command runs, for commands that are always slow.
+    Immediately { ephemeral: bool },
+}
+
+impl Default for Defer {
+    fn default() -> Self {
+        Defer::AfterDelay { ephemeral: false }
+    }
+}
+
+/// A message sent in response to a command: as the initial response, an edit of it or a follow-up.
+///
+/// Prefix commands get their replies as messages in the channel, where `ephemeral` has no effect.
+///
+/// Replies ping nobody by default, so that text from users (`!echo @everyone`) can't be used
+/// to ping a role or the whole server. Commands that should ping opt in with `allowed_mentions`.
+#[derive(Debug, Clone, Default)]
+pub struct Reply {
+    content: Option<String>,
+    embeds: Vec<CreateEmbed>,
+    components: Vec<CreateActionRow>,
+    attachments: Vec<CreateAttachment>,
+    allowed_mentions: CreateAllowedMentions,
+    ephemeral: bool,
+}
+
+impl Reply {
+    /// Creates a reply with the given text.
+    pub fn new(content: impl Into<String>) -> Self {
+        Reply::default().content(content)
+    }
+
+    /// Sets the text of the reply.
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+
+    /// Adds an embed to the reply.
+    pub fn embed(mut self, embed: CreateEmbed) -> Self {
+        self.embeds.push(embed);
+        self
+    }
+
+    /// Sets the components (buttons, select menus) of the reply.
+    pub fn components(mut self, components: Vec<CreateActionRow>) -> Self {
+        self.components = components;
+        self
+    }
+
+    /// Attaches a file to the reply, e.g. `CreateAttachment::bytes(data, "report.csv")`.
+    pub fn attachment(mut self, attachment: CreateAttachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    /// Sets the mentions of the reply that ping, e.g. `CreateAllowedMentions::new().users(vec![id])`.
+    /// Default is none.
+    pub fn allowed_mentions(mut self, allowed_mentions: CreateAllowedMentions) -> Self {
+        self.allowed_mentions = allowed_mentions;
+        self
+    }
+
+    /// Makes the reply visible to the invoking user only.
+    pub fn ephemeral(mut self, ephemeral: bool) -> Self {
+        self.ephemeral = ephemeral;
+        self
+    }
+
+    fn into_message(self) -> CreateInteractionResponseMessage {
+        let mut message = CreateInteractionResponseMessage::new()
+            .embeds(self.embeds)
+            .components(self.components)
+            .allowed_mentions(self.allowed_mentions)
+            .add_files(self.attachments)
+            .ephemeral(self.ephemeral);
+        if let Some(content) = self.content {
+            message = message.content(content);
+        }
+        message
+    }
+
+    fn into_edit(self) -> EditInteractionResponse {
+        let mut edit = EditInteractionResponse::new()
+            .embeds(self.embeds)
+            .components(self.components)
+            .allowed_mentions(self.allowed_mentions);
+        for attachment in self.attachments {
+            edit = edit.new_attachment(attachment);
+        }
+        if let Some(content) = self.content {
+            edit = edit.content(content);
+        }
+        edit
+    }
+
+    fn into_followup(self) -> CreateInteractionResponseFollowup {
+        let mut followup = CreateInteractionResponseFollowup::new()
+            .embeds(self.embeds)
+            .components(self.components)
+            .allowed_mentions(self.allowed_mentions)
+            .add_files(self.attachments)
+            .ephemeral(self.ephemeral);
+        if let Some(content) = self.content {
+            followup = followup.content(content);
+        }
+        followup
+    }
+
+    fn into_channel_message(self) -> CreateMessage {
+        let mut message = CreateMessage::new()
+            .embeds(self.embeds)
+            .components(self.components)
+            .allowed_mentions(self.allowed_mentions)
+            .add_files(self.attachments);
+        if let Some(content) = self.content {
+            message = message.content(content);
+        }
+        message
+    }
+
+    fn into_channel_edit(self) -> EditMessage {
+        let mut edit = EditMessage::new()
+            .embeds(self.embeds)
+            .components(self.components)
+            .allowed_mentions(self.allowed_mentions);
+        for attachment in self.attachments {
+            edit = edit.new_attachment(attachment);
+        }
+        if let Some(content) = self.content {
+            edit = edit.content(content);
+        }
+        edit
+    }
+}
+
+impl From<&str> for Reply {
+    fn from(content: &str) -> Self {
+        Reply::new(content)
+    }
+}
+
+impl From<String> for Reply {
+    fn from(content: String) -> Self {
+        Reply::new(content)
+    }
+}
+
+/// Where the response to a command stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseState {
+    /// Nothing was sent yet.
+    Pending,
+    /// A deferred response was sent and waits for its content.
+    /// For prefix commands, the bot is shown as typing.
+    Deferred { ephemeral: bool },
+    /// The initial response was sent; further replies are follow-ups.
+    Responded,
+    /// The reply to a prefix command was sent; further replies are new messages.
+    Replied(MessageId),
+}
+
+/// What a `Responder` responds to.
+#[derive(Clone, Copy)]
+enum Target<'a> {
+    Interaction(&'a CommandInteraction),
+    Message(&'a Message),
+}
+
+/// The response handle of a command invocation.
+///
+/// It keeps track of what was already sent, so `send` always does the right thing:
+/// the initial response, filling in a deferred response, or a follow-up. Commands should
+/// respond through it rather than through the interaction, since the dispatcher may
+/// defer the response while they run, and since prefix commands have no interaction:
+/// their replies are sent as messages in the channel instead.
+pub struct Responder<'a> {
+    ctx: &'a Context,
+    target: Target<'a>,
+    locale: String,
+    style: Arc<EmbedStyle>,
+    state: Mutex<ResponseState>,
+}
+
+impl<'a> Responder<'a> {
+    /// Creates the response handle of an interaction nobody responded to yet.
+    ///
+    /// `style` is applied to the embeds created with `embed` and `send_embed`.
+    pub fn new(ctx: &'a Context, interaction: &'a CommandInteraction, style: Arc<EmbedStyle>) -> Self {
+        Responder {
+            ctx,
+            target: Target::Interaction(interaction),
+            locale: interaction.locale.clone(),
+            style,
+            state: Mutex::new(ResponseState::Pending),
+        }
+    }
+
+    /// Creates the response handle of a prefix command, which replies to `message` in its channel.
+    ///
+    /// Messages don't carry the locale of their author: the guild's preferred locale is used.
+    pub fn from_message(ctx: &'a Context, message: &'a Message, style: Arc<EmbedStyle>) -> Self {
+        let locale = message
+            .guild(&ctx.cache)
+            .map_or_else(|| FALLBACK_LOCALE.to_string(), |guild| guild.preferred_locale.clone());
+        Responder {
+            ctx,
+            target: Target::Message(message),
+            locale,
+            style,
+            state: Mutex::new(ResponseState::Pending),
+        }
+    }
+
+    /// Creates an empty embed with the bot-wide style.
+    pub fn embed(&self) -> CreateEmbed {
+        self.style.embed()
+    }
+
+    /// The locale of the invoking user's client, e.g. `fr`.
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Translates `key` into the invoking user's language. See `i18n::translate`.
+    pub fn tr(&self, key: &str) -> String {
+        translate(self.locale(), key, &[])
+    }
+
+    /// Translates `key` into the invoking user's language, filling in its `{name}` placeholders.
+    ///
+    /// ```
+    /// response.send(response.tr_with("sum.result", &[("result", &(args.a + args.b))])).await?;
+    /// ```
+    pub fn tr_with(&self, key: &str, args: &[(&str, &(dyn Display + Sync))]) -> String {
+        translate(self.locale(), key, args)
+    }
+
+    /// Sends a text reply only the invoking user can see. See `send`.
+    pub async fn send_ephemeral(&self, content: impl Into<String>) -> Result<(), serenity::Error> {
+        self.send(Reply::new(content).ephemeral(true)).await
+    }
+
+    /// Sends a reply made of one embed with the bot-wide style, filled in by `build`. See `send`.
+    ///
+    /// ```
+    /// response.send_embed(|embed| embed.title("Server info").field("Members", "42", true)).await?;
+    /// ```
+    pub async fn send_embed(&self, build: impl FnOnce(CreateEmbed) -> CreateEmbed) -> Result<(), serenity::Error> {
+        self.send(Reply::default().embed(build(self.embed()))).await
+    }
+
+    /// Sends a reply: as the initial response, in place of the deferred response,
+    /// or as a follow-up if the command already responded.
+    ///
+    /// When the reply's visibility differs from the one chosen when deferring, the deferred
+    /// response is deleted and the reply sent as a follow-up instead.
+    pub async fn send(&self, reply: impl Into<Reply>) -> Result<(), serenity::Error> {
+        let reply = reply.into();
+        let mut state = self.state.lock().await;
+        let interaction = match self.target {
+            Target::Interaction(interaction) => interaction,
+            Target::Message(message) => {
+                self.send_message(message, &mut state, reply).await?;
+                return Ok(());
+            }
+        };
+        match *state {
+            ResponseState::Pending => {
+                interaction
+                    .create_response(self.ctx, CreateInteractionResponse::Message(reply.into_message()))
+                    .await?;
+            }
+            ResponseState::Deferred { ephemeral } if ephemeral == reply.ephemeral => {
+                interaction.edit_response(self.ctx, reply.into_edit()).await?;
+            }
+            ResponseState::Deferred { .. } => {
+                interaction.delete_response(self.ctx).await?;
+                interaction.create_followup(self.ctx, reply.into_followup()).await?;
+            }
+            ResponseState::Responded | ResponseState::Replied(_) => {
+                interaction.create_followup(self.ctx, reply.into_followup()).await?;
+            }
+        }
+        *state = ResponseState::Responded;
+        Ok(())
+    }
+
+    /// Sends `reply` in the channel of a prefix command: the first one as a reply to the
+    /// command message, the next ones as plain messages.
+    ///
+    /// The reply is still sent if the command message was deleted meanwhile (e.g. by `/purge`).
+    async fn send_message(&self, message: &Message, state: &mut ResponseState, reply: Reply) -> Result<Message, serenity::Error> {
+        let mut create = reply.into_channel_message();
+        if !matches!(state, ResponseState::Replied(_)) {
+            create = create.reference_message(MessageReference::from(message).fail_if_not_exists(false));
+        }
+        let sent = message.channel_id.send_message(self.ctx, create).await?;
+        if !matches!(state, ResponseState::Replied(_)) {
+            *state = ResponseState::Replied(sent.id);
+        }
+        Ok(sent)
+    }
+
+    /// Replaces the content of the initial response, or sends it if there is none yet.
+    pub async fn edit(&self, reply: impl Into<Reply>) -> Result<(), serenity::Error> {
+        let reply = reply.into();
+        let mut state = self.state.lock().await;
+        match (self.target, *state) {
+            (Target::Message(message), ResponseState::Replied(id)) => {
+                message.channel_id.edit_message(self.ctx, id, reply.into_channel_edit()).await?;
+            }
+            (Target::Message(message), _) => {
+                self.send_message(message, &mut state, reply).await?;
+            }
+            (Target::Interaction(interaction), ResponseState::Pending) => {
+                interaction
+                    .create_response(self.ctx, CreateInteractionResponse::Message(reply.into_message()))
+                    .await?;
+                *state = ResponseState::Responded;
+            }
+            (Target::Interaction(interaction), _) => {
+                interaction.edit_response(self.ctx, reply.into_edit()).await?;
+                *state = ResponseState::Responded;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends a follow-up message, deferring first if nothing was sent yet.
+    ///
+    /// The first follow-up after a deferral takes the place of the loading message, with its
+    /// visibility; when the reply's differs, the deferred response is deleted first, as in `send`.
+    pub async fn followup(&self, reply: impl Into<Reply>) -> Result<Message, serenity::Error> {
+        let reply = reply.into();
+        let mut state = self.state.lock().await;
+        let interaction = match self.target {
+            Target::Interaction(interaction) => interaction,
+            Target::Message(message) => return self.send_message(message, &mut state, reply).await,
+        };
+        self.defer_locked(&mut state, reply.ephemeral).await?;
+        if let ResponseState::Deferred { ephemeral } = *state
+            && ephemeral != reply.ephemeral
+        {
+            interaction.delete_response(self.ctx).await?;
+        }
+        let sent = interaction.create_followup(self.ctx, reply.into_followup()).await?;
+        *state = ResponseState::Responded;
+        Ok(sent)
+    }
+
+    /// Defers the response, so the command has 15 minutes to `send` it.
+    /// For prefix commands, this shows the bot as typing.
+    ///
+    /// Does nothing if the command already responded or deferred.
+    pub async fn defer(&self, ephemeral: bool) -> Result<(), serenity::Error> {
+        let mut state = self.state.lock().await;
+        self.defer_locked(&mut state, ephemeral).await
+    }
+
+    /// Defers the response while the caller holds the `state` lock. See `defer`.
+    async fn defer_locked(&self, state: &mut ResponseState, ephemeral: bool) -> Result<(), serenity::Error> {
+        if *state != ResponseState::Pending {
+            return Ok(());
+        }
+        match self.target {
+            Target::Interaction(interaction) => {
+                let response = CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(ephemeral));
+                interaction.create_response(self.ctx, response).await?;
+            }
+            Target::Message(message) => message.channel_id.broadcast_typing(self.ctx).await?,
+        }
+        *state = ResponseState::Deferred { ephemeral };
+        Ok(())
+    }
+
+    /// Presents a modal. This must be the initial response, and fails for prefix commands.
+    pub async fn modal(&self, modal: CreateModal) -> Result<(), serenity::Error> {
+        let Target::Interaction(interaction) = self.target else {
+            return Err(serenity::Error::Other("modals can only be shown in response to an interaction"));
+        };
+        let mut state = self.state.lock().await;
+        interaction
+            .create_response(self.ctx, CreateInteractionResponse::Modal(modal))
+            .await?;
+        *state = ResponseState::Responded;
+        Ok(())
+    }
+
+    /// Runs `command`, deferring its response as configured by `defer`.
+    pub async fn with_defer(&self, defer: Defer, command: impl Future<Output = CommandResult>) -> CommandResult {
+        match defer {
+            Defer::Never => command.await,
+            Defer::Immediately { ephemeral } => {
+                self.defer(ephemeral).await?;
+                command.await
+            }
+            Defer::AfterDelay { ephemeral } => {
+                tokio::pin!(command);
+                tokio::select! {
+                    result = &mut command => result,
+                    _ = tokio::time::sleep(AUTO_DEFER_DELAY) => {
+                        // If the state is locked, the command is responding right now and needs no
+                        // deferral. Waiting for the lock would deadlock: `command` holds it, and
+                        // isn't polled until this branch is done.
+                        if let Ok(mut state) = self.state.try_lock()
+                            && let Err(err) = self.defer_locked(&mut state, ephemeral).await
+                        {
+                            eprintln!("Could not defer {}: {err:?}", self.name());
+                        }
+                        command.await
+                    }
+                }
+            }
+        }
+    }
+
+    /// The name of the command being responded to, for logs.
+    fn name(&self) -> String {
+        match self.target {
+            Target::Interaction(interaction) => format!("/{}", interaction.data.name),
+            Target::Message(message) => format!("prefix command {}", message.link()),
+        }
+    }
+}
+
+#[async_trait]
+impl ErrorReply for Responder<'_> {
+    async fn reply_ephemeral(&self, _ctx: &Context, content: String) -> Result<(), serenity::Error> {
+        self.send_ephemeral(content).await
+    }
+
+    fn invoker(&self) -> &User {
+        match self.target {
+            Target::Interaction(interaction) => &interaction.user,
+            Target::Message(message) => &message.author,
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+
+use serenity::all::*;
+use crate::config::CONFIG;
+use crate::guild_settings::{guild_settings, GuildSettings, GuildSettingsStore};
+use crate::registry::registry;
+use crate::state::StateExt;
+use crate::sync::{sync_commands, CommandChange, SyncTarget};
+
+/// Where a command is registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandScope {
+    /// Registered globally, available in every guild and in DMs.
+    Global,
+    /// Registered only in the listed guilds.
+    Guilds(&'static [GuildId]),
+    /// Registered only in the development guild (`DEV_GUILD_ID`), and nowhere if it's not set.
+    Dev,
+}
+
+impl CommandScope {
+    /// Whether a command with this scope belongs in `guild_id`, outside of development mode.
+    fn includes(&self, guild_id: GuildId) -> bool {
+        matches!(self, CommandScope::Guilds(guilds) if guilds.contains(&guild_id))
+    }
+
+    /// Whether a command with this scope is registered where `guild_id` (`None` for DMs) is.
+    ///
+    /// Used for prefix commands, which Discord doesn't filter.
+    pub fn available_in(&self, guild_id: Option<GuildId>) -> bool {
+        match CONFIG.dev_guild {
+            Some(dev_guild) => guild_id == Some(dev_guild),
+            None => match self {
+                CommandScope::Global => true,
+                _ => guild_id.is_some_and(|guild_id| self.includes(guild_id)),
+            },
+        }
+    }
+}
+
+/// Every command of every kind in the registry (so without the disabled modules), with its name and scope.
+fn all_scoped_commands() -> Vec<(&'static str, CommandScope, CreateCommand)> {
+    let registry = registry();
+    registry
+        .slash_commands()
+        .iter()
+        .map(|cmd| (cmd.name(), cmd.scope(), cmd.register()))
+        .chain(registry.user_commands().iter().map(|cmd| (cmd.name(), cmd.scope(), cmd.register())))
+        .chain(registry.message_commands().iter().map(|cmd| (cmd.name(), cmd.scope(), cmd.register())))
+        .collect()
+}
+
+/// The commands to register globally.
+///
+/// This is empty in development mode, where everything goes to the development guild.
+pub fn global_commands() -> Vec<CreateCommand> {
+    if CONFIG.dev_guild.is_some() {
+        return vec![];
+    }
+    all_scoped_commands()
+        .into_iter()
+        .filter(|(_, scope, _)| *scope == CommandScope::Global)
+        .map(|(_, _, command)| command)
+        .collect()
+}
+
+/// The commands to register in `guild_id`, leaving out the ones its `settings` disable.
+///
+/// In development mode, this is every command for the development guild and nothing elsewhere.
+pub fn guild_commands(guild_id: GuildId, settings: &GuildSettings) -> Vec<CreateCommand> {
+    let in_guild = |scope: &CommandScope| match CONFIG.dev_guild {
+        Some(dev_guild) => dev_guild == guild_id,
+        None => scope.includes(guild_id),
+    };
+    all_scoped_commands()
+        .into_iter()
+        .filter(|(name, scope, _)| in_guild(scope) && settings.is_enabled(name))
+        .map(|(_, _, command)| command)
+        .collect()
+}
+
+/// The guilds that get their own command set, i.e. the ones `sync_guild_commands` cares about.
+pub fn scoped_guilds() -> HashSet<GuildId> {
+    match CONFIG.dev_guild {
+        Some(dev_guild) => HashSet::from([dev_guild]),
+        None => all_scoped_commands()
+            .into_iter()
+            .filter_map(|(_, scope, _)| match scope {
+                CommandScope::Guilds(guilds) => Some(guilds.iter().copied()),
+                _ => None,
+            })
+            .flatten()
+            .collect(),
+    }
+}
+
+/// Syncs the global commands with Discord.
+///
+/// Global commands can take up to an hour to show up in every client.
+/// Nothing is sent in development mode, so the global commands of the application are left as they are.
+pub async fn sync_global_commands(ctx: &Context) -> Result<Vec<CommandChange>, serenity::Error> {
+    if CONFIG.dev_guild.is_some() {
+        return Ok(vec![]);
+    }
+    sync_commands(ctx, SyncTarget::Global, global_commands()).await
+}
+
+/// Syncs the guild-scoped commands of `guild_id` with Discord.
+///
+/// Guilds that have no guild-scoped commands and never had any are left untouched; the
+/// ones that no longer have any (e.g. the development guild once `DEV_GUILD_ID` is unset)
+/// get their leftover commands deleted. The commands the guild disabled with
+/// `/commands disable` are removed from its picker; global commands can't be removed
+/// for a single guild, so those are only blocked when used.
+pub async fn sync_guild_commands(ctx: &Context, guild_id: GuildId) -> Result<Vec<CommandChange>, serenity::Error> {
+    let settings = guild_settings(ctx, Some(guild_id)).await;
+    let scoped = scoped_guilds().contains(&guild_id);
+    if !scoped && !settings.has_guild_commands {
+        return Ok(vec![]);
+    }
+
+    let commands = if scoped { guild_commands(guild_id, &settings) } else { vec![] };
+    let has_guild_commands = !commands.is_empty();
+    let changes = sync_commands(ctx, SyncTarget::Guild(guild_id), commands).await?;
+    if has_guild_commands != settings.has_guild_commands
+        && let Ok(store) = ctx.state::<GuildSettingsStore>().await
+    {
+        store.update(guild_id, |settings| settings.has_guild_commands = has_guild_commands).await?;
+    }
+    Ok(changes)
+}
@@ -0,0 +1,96 @@
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serenity::all::*;
+use async_trait::async_trait;
+use serenity::prelude::TypeMapKey;
+
+/// Shared application state: one value per type (database pool, HTTP client, caches...).
+///
+/// The state is built in `main.rs` and handed to the client, so that commands, handlers and
+/// event handlers can request what they need by type with `ctx.state::<T>()`:
+///
+/// ```
+/// let state = AppState::builder()
+///     .with(reqwest::Client::new())
+///     .with(MyDatabase::connect(&url).await)
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct AppState {
+    values: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl AppState {
+    /// Starts an empty state.
+    pub fn builder() -> AppStateBuilder {
+        AppStateBuilder::default()
+    }
+
+    /// Returns the value of type `T`, if one was provided.
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|value| value.downcast().ok())
+    }
+}
+
+impl TypeMapKey for AppState {
+    type Value = Arc<AppState>;
+}
+
+/// A builder for `AppState`.
+#[derive(Default)]
+pub struct AppStateBuilder {
+    state: AppState,
+}
+
+impl AppStateBuilder {
+    /// Provides a value, replacing any earlier value of the same type.
+    ///
+    /// Values are shared between concurrent handlers: use interior mutability
+    /// (`Mutex`, `RwLock`, atomics) for the parts that change.
+    pub fn with<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.state.values.insert(TypeId::of::<T>(), Arc::new(value));
+        self
+    }
+
+    /// Builds the `AppState`.
+    pub fn build(self) -> AppState {
+        self.state
+    }
+}
+
+/// An error raised when a handler requests state that was not provided in `main.rs`.
+#[derive(Debug, Clone)]
+pub struct StateError(&'static str);
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no `{}` in the application state", self.0)
+    }
+}
+
+impl std::error::Error for StateError {}
+
+/// Access to the application state from the Serenity `Context`.
+#[async_trait]
+pub trait StateExt {
+    /// Returns the shared value of type `T`.
+    ///
+    /// The error converts into an internal `CommandError`, so `?` works in handlers.
+    async fn state<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, StateError>;
+}
+
+#[async_trait]
+impl StateExt for Context {
+    async fn state<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, StateError> {
+        let data = self.data.read().await;
+        data.get::<AppState>()
+            .and_then(|state| state.get::<T>())
+            .ok_or(StateError(type_name::<T>()))
+    }
+}
@@ -0,0 +1,72 @@
+use serenity::all::*;
+
+/// The look shared by every embed the bot sends, configured once in `main.rs`.
+///
+/// Commands get a styled embed from `Responder::embed` (or `EmbedStyle::embed` through
+/// `ctx.state::<EmbedStyle>()` elsewhere) and only fill in their own content.
+#[derive(Debug, Clone)]
+pub struct EmbedStyle {
+    colour: Colour,
+    footer: Option<String>,
+    timestamp: bool,
+}
+
+impl Default for EmbedStyle {
+    fn default() -> Self {
+        EmbedStyle {
+            colour: Colour::BLURPLE,
+            footer: None,
+            timestamp: false,
+        }
+    }
+}
+
+impl EmbedStyle {
+    /// Starts from the default style: blurple, no footer, no timestamp.
+    pub fn new() -> Self {
+        EmbedStyle::default()
+    }
+
+    /// Sets the colour of the embeds.
+    pub fn colour(mut self, colour: impl Into<Colour>) -> Self {
+        self.colour = colour.into();
+        self
+    }
+
+    /// Sets the footer text of the embeds.
+    pub fn footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
+    /// Whether embeds show the time they were sent.
+    pub fn timestamp(mut self, timestamp: bool) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Creates an empty embed with this style.
+    pub fn embed(&self) -> CreateEmbed {
+        let mut embed = CreateEmbed::new().colour(self.colour);
+        if let Some(footer) = &self.footer {
+            embed = embed.footer(CreateEmbedFooter::new(footer));
+        }
+        if self.timestamp {
+            embed = embed.timestamp(Timestamp::now());
+        }
+        embed
+    }
+}
+
+/// The most characters an embed field value can hold.
+pub const MAX_FIELD_LENGTH: usize = 1024;
+
+/// Shortens `text` to fit in an embed field.
+pub fn truncate_field(text: String) -> String {
+    if text.chars().count() <= MAX_FIELD_LENGTH {
+        return text;
+    }
+    let mut text: String = text.chars().take(MAX_FIELD_LENGTH - 1).collect();
+    text.push('…');
+    text
+}
@@ -0,0 +1,215 @@
+use serenity::all::*;
+use async_trait::async_trait;
+use crate::args::CommandArgs;
+use crate::checks::Check;
+use crate::error::CommandResult;
+use crate::i18n::{in_scope, localize_option};
+use crate::invocation::Invocation;
+use crate::response::Responder;
+
+/// A leaf of a command tree, such as `get` in `/config get`.
+///
+/// Subcommands are not registered on their own: they are returned from the
+/// `SlashCommand::subcommands` method of their parent command, which takes care of
+/// registering them as `SubCommand` options and routing interactions to them.
+#[async_trait]
+pub trait Subcommand: Sync + Send {
+    /// The typed arguments of this subcommand.
+    type Args: CommandArgs;
+
+    /// The name of the subcommand (e.g. `"get"`).
+    fn name(&self) -> &'static str;
+
+    /// A short description of what the subcommand does.
+    fn description(&self) -> &'static str;
+
+    /// (Optional) Returns the options of this subcommand.
+    ///
+    /// Default is the options declared by `Self::Args`.
+    fn options(&self) -> Vec<CreateCommandOption> {
+        Self::Args::options()
+    }
+
+    /// (Optional) Returns the checks that must pass before this subcommand runs,
+    /// in addition to the ones of its parent command.
+    ///
+    /// Unlike the parent's checks, these are only enforced by the dispatcher:
+    /// Discord has no per-subcommand permissions. Default is no checks.
+    fn checks(&self) -> Vec<Check> {
+        vec![]
+    }
+
+    /// The logic to be executed when this subcommand is invoked.
+    ///
+    /// # Arguments
+    /// * `ctx` - The bot context provided by Serenity.
+    /// * `invocation` - Who used the command and where, see `SlashCommand::run`.
+    /// * `response` - The handle to respond with, see `SlashCommand::run`.
+    /// * `args` - The parsed arguments of the subcommand.
+    async fn run(&self, ctx: &Context, invocation: &Invocation<'_>, response: &Responder<'_>, args: Self::Args) -> CommandResult;
+
+    /// (Optional) Returns autocomplete suggestions for the option the user is typing.
+    ///
+    /// See `SlashCommand::autocomplete`.
+    async fn autocomplete(
+        &self,
+        _ctx: &Context,
+        _interaction: &CommandInteraction,
+        _focused: AutocompleteOption<'_>,
+    ) -> Vec<AutocompleteChoice> {
+        vec![]
+    }
+}
+
+/// Object-safe view of a `Subcommand`.
+///
+/// This is implemented automatically for every `Subcommand`.
+#[async_trait]
+pub trait DynSubcommand: Sync + Send {
+    /// See `Subcommand::name`.
+    fn name(&self) -> &'static str;
+
+    /// See `Subcommand::checks`.
+    fn checks(&self) -> Vec<Check>;
+
+    /// Builds the `SubCommand` option registering this subcommand.
+    fn register(&self) -> CreateCommandOption;
+
+    /// Parses `options` into the subcommand arguments and runs it.
+    async fn dispatch(
+        &self,
+        ctx: &Context,
+        invocation: &Invocation<'_>,
+        response: &Responder<'_>,
+        options: &[ResolvedOption<'_>],
+    ) -> CommandResult;
+
+    /// See `Subcommand::autocomplete`.
+    async fn autocomplete(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        focused: AutocompleteOption<'_>,
+    ) -> Vec<AutocompleteChoice>;
+}
+
+#[async_trait]
+impl<T: Subcommand> DynSubcommand for T {
+    fn name(&self) -> &'static str {
+        Subcommand::name(self)
+    }
+
+    fn checks(&self) -> Vec<Check> {
+        Subcommand::checks(self)
+    }
+
+    fn register(&self) -> CreateCommandOption {
+        let name = Subcommand::name(self);
+        let option = CreateCommandOption::new(CommandOptionType::SubCommand, name, self.description())
+            .set_sub_options(in_scope(name, || self.options()));
+        localize_option(option, name)
+    }
+
+    async fn dispatch(
+        &self,
+        ctx: &Context,
+        invocation: &Invocation<'_>,
+        response: &Responder<'_>,
+        options: &[ResolvedOption<'_>],
+    ) -> CommandResult {
+        let args = T::Args::parse(options)?;
+        self.run(ctx, invocation, response, args).await
+    }
+
+    async fn autocomplete(
+        &self,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+        focused: AutocompleteOption<'_>,
+    ) -> Vec<AutocompleteChoice> {
+        Subcommand::autocomplete(self, ctx, interaction, focused).await
+    }
+}
+
+/// An entry of a command tree: either a subcommand or a group of subcommands.
+pub enum CommandNode {
+    /// A subcommand directly under the command, e.g. `/config get`.
+    Subcommand(&'static dyn DynSubcommand),
+    /// A named group of subcommands, e.g. `case` in `/mod case view`.
+    Group {
+        name: &'static str,
+        description: &'static str,
+        subcommands: Vec<&'static dyn DynSubcommand>,
+    },
+}
+
+impl CommandNode {
+    /// Creates a group of subcommands.
+    pub fn group(
+        name: &'static str,
+        description: &'static str,
+        subcommands: Vec<&'static dyn DynSubcommand>,
+    ) -> Self {
+        CommandNode::Group { name, description, subcommands }
+    }
+
+    /// Builds the `SubCommand` or `SubCommandGroup` option for this node.
+    pub fn register(&self) -> CreateCommandOption {
+        match self {
+            CommandNode::Subcommand(subcommand) => subcommand.register(),
+            CommandNode::Group { name, description, subcommands } => {
+                let option = CreateCommandOption::new(CommandOptionType::SubCommandGroup, *name, *description)
+                    .set_sub_options(in_scope(name, || subcommands.iter().map(|s| s.register()).collect::<Vec<_>>()));
+                localize_option(option, name)
+            }
+        }
+    }
+}
+
+/// Finds the subcommand invoked by `options` in a command tree.
+///
+/// Returns the subcommand together with its own options.
+pub fn find_subcommand<'a, 'b>(
+    nodes: &[CommandNode],
+    options: &'b [ResolvedOption<'a>],
+) -> Option<(&'static dyn DynSubcommand, &'b [ResolvedOption<'a>])> {
+    let option = options.first()?;
+    match &option.value {
+        ResolvedValue::SubCommand(sub_options) => nodes.iter().find_map(|node| match node {
+            CommandNode::Subcommand(subcommand) if subcommand.name() == option.name => {
+                Some((*subcommand, sub_options.as_slice()))
+            }
+            _ => None,
+        }),
+        ResolvedValue::SubCommandGroup(group_options) => {
+            let subcommands = nodes.iter().find_map(|node| match node {
+                CommandNode::Group { name, subcommands, .. } if *name == option.name => Some(subcommands),
+                _ => None,
+            })?;
+            let inner = group_options.first()?;
+            let ResolvedValue::SubCommand(sub_options) = &inner.value else {
+                return None;
+            };
+            let subcommand = subcommands.iter().find(|s| s.name() == inner.name)?;
+            Some((*subcommand, sub_options.as_slice()))
+        }
+        _ => None,
+    }
+}
+
+/// Builds the full name of an invoked command, e.g. `math convert celsius`.
+pub fn full_name(command: &str, options: &[ResolvedOption<'_>]) -> String {
+    let mut name = command.to_string();
+    let mut options = options;
+    while let Some(option) = options.first() {
+        match &option.value {
+            ResolvedValue::SubCommand(inner) | ResolvedValue::SubCommandGroup(inner) => {
+                name.push(' ');
+                name.push_str(option.name);
+                options = inner;
+            }
+            _ => break,
+        }
+    }
+    name
+}
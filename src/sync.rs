@@ -0,0 +1,390 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+use serenity::all::*;
+
+/// A set of registered commands: the global ones, or those of one guild.
+#[derive(Debug, Clone, Copy)]
+pub enum SyncTarget {
+    Global,
+    Guild(GuildId),
+}
+
+impl fmt::Display for SyncTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncTarget::Global => write!(f, "global commands"),
+            SyncTarget::Guild(guild_id) => write!(f, "commands of guild {guild_id}"),
+        }
+    }
+}
+
+impl SyncTarget {
+    async fn fetch(self, ctx: &Context) -> Result<Vec<Command>, serenity::Error> {
+        match self {
+            SyncTarget::Global => Command::get_global_commands_with_localizations(&ctx.http).await,
+            SyncTarget::Guild(guild_id) => guild_id.get_commands_with_localizations(&ctx.http).await,
+        }
+    }
+
+    async fn create(self, ctx: &Context, command: CreateCommand) -> Result<Command, serenity::Error> {
+        match self {
+            SyncTarget::Global => Command::create_global_command(ctx, command).await,
+            SyncTarget::Guild(guild_id) => guild_id.create_command(ctx, command).await,
+        }
+    }
+
+    async fn edit(self, ctx: &Context, id: CommandId, command: CreateCommand) -> Result<Command, serenity::Error> {
+        match self {
+            SyncTarget::Global => Command::edit_global_command(ctx, id, command).await,
+            SyncTarget::Guild(guild_id) => guild_id.edit_command(ctx, id, command).await,
+        }
+    }
+
+    async fn delete(self, ctx: &Context, id: CommandId) -> Result<(), serenity::Error> {
+        match self {
+            SyncTarget::Global => Command::delete_global_command(&ctx.http, id).await,
+            SyncTarget::Guild(guild_id) => guild_id.delete_command(&ctx.http, id).await,
+        }
+    }
+}
+
+/// A change applied to the registered commands while syncing.
+#[derive(Debug, Clone)]
+pub enum CommandChange {
+    /// The command did not exist and was created.
+    Created(String),
+    /// The command existed with different data and was edited; holds what changed.
+    Edited(String, Vec<String>),
+    /// The command is no longer defined and was deleted.
+    Deleted(String),
+}
+
+impl fmt::Display for CommandChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandChange::Created(name) => write!(f, "+ {name}"),
+            CommandChange::Edited(name, fields) => write!(f, "~ {name} ({})", fields.join(", ")),
+            CommandChange::Deleted(name) => write!(f, "- {name}"),
+        }
+    }
+}
+
+/// The top-level command fields compared when syncing.
+///
+/// Fields Discord fills in on its own (ids, versions, contexts) are ignored.
+const COMPARED_FIELDS: [&str; 9] = [
+    "type",
+    "name",
+    "name_localizations",
+    "description",
+    "description_localizations",
+    "options",
+    "default_member_permissions",
+    "dm_permission",
+    "nsfw",
+];
+
+/// Brings a serialized `CreateCommand` or `Command` to a comparable form.
+///
+/// Both serialize to the Discord payload format, but differ in which defaults they spell out
+/// (`null` vs absent, `false` vs absent, `{}` vs `null`...).
+fn normalize(command: Value) -> Map<String, Value> {
+    let Value::Object(mut command) = command else {
+        return Map::new();
+    };
+    command.retain(|key, _| COMPARED_FIELDS.contains(&key.as_str()));
+    if !command.contains_key("type") {
+        command.insert("type".into(), Value::from(1));
+    }
+    // `dm_permission` defaults to true, so only an explicit `false` is meaningful.
+    let dm_disabled = command.remove("dm_permission") == Some(Value::Bool(false));
+    let mut command = match prune(Value::Object(command)) {
+        Value::Object(command) => command,
+        _ => Map::new(),
+    };
+    if dm_disabled {
+        command.insert("dm_permission".into(), Value::Bool(false));
+    }
+    command
+}
+
+/// Recursively removes defaulted values (`null`, `false`, `""`, `[]`, `{}`) and makes numbers comparable.
+fn prune(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, prune(value)))
+                .filter(|(_, value)| !is_default(value))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(prune).collect()),
+        Value::Number(n) => n.as_f64().map_or(Value::Number(n), Value::from),
+        other => other,
+    }
+}
+
+fn is_default(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(values) => values.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+/// A readable name for a normalized command: `/name` for slash commands, the plain name otherwise.
+fn display_name(command: &Map<String, Value>) -> String {
+    let name = command.get("name").and_then(Value::as_str).unwrap_or_default();
+    if command.get("type").and_then(Value::as_f64) == Some(1.0) {
+        format!("/{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Lists what differs between two normalized commands.
+fn describe_changes(old: &Map<String, Value>, new: &Map<String, Value>) -> Vec<String> {
+    COMPARED_FIELDS
+        .iter()
+        .filter(|field| old.get(**field) != new.get(**field))
+        .map(|field| match *field {
+            "options" => describe_option_changes(old.get("options"), new.get("options")),
+            "description" => format!(
+                "description {} -> {}",
+                old.get("description").unwrap_or(&Value::Null),
+                new.get("description").unwrap_or(&Value::Null),
+            ),
+            field => field.to_string(),
+        })
+        .collect()
+}
+
+/// Lists added (`+`), removed (`-`) and changed (`~`) options by name.
+fn describe_option_changes(old: Option<&Value>, new: Option<&Value>) -> String {
+    let named = |options: Option<&Value>| -> Vec<(String, Value)> {
+        options
+            .and_then(Value::as_array)
+            .map(|options| {
+                options
+                    .iter()
+                    .map(|o| (o["name"].as_str().unwrap_or_default().to_string(), o.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let (old, new) = (named(old), named(new));
+
+    let mut parts = Vec::new();
+    for (name, option) in &new {
+        match old.iter().find(|(old_name, _)| old_name == name) {
+            None => parts.push(format!("+{name}")),
+            Some((_, old_option)) if old_option != option => parts.push(format!("~{name}")),
+            Some(_) => {}
+        }
+    }
+    for (name, _) in &old {
+        if !new.iter().any(|(new_name, _)| new_name == name) {
+            parts.push(format!("-{name}"));
+        }
+    }
+    if parts.is_empty() {
+        parts.push("order".to_string());
+    }
+    format!("options {}", parts.join(" "))
+}
+
+/// Brings the commands registered in `target` in line with `desired`.
+///
+/// The existing commands are fetched and compared with the desired ones: only missing commands
+/// are created, changed ones edited and stale ones deleted, so unchanged commands keep their IDs
+/// and no request is spent on them. Commands are matched by name and type.
+///
+/// Returns the applied changes.
+pub async fn sync_commands(
+    ctx: &Context,
+    target: SyncTarget,
+    desired: Vec<CreateCommand>,
+) -> Result<Vec<CommandChange>, serenity::Error> {
+    let mut existing: Vec<(CommandId, Map<String, Value>)> = target
+        .fetch(ctx)
+        .await?
+        .into_iter()
+        .map(|command| Ok((command.id, normalize(serde_json::to_value(&command)?))))
+        .collect::<Result<_, serde_json::Error>>()?;
+
+    let mut changes = Vec::new();
+    for command in desired {
+        let new = normalize(serde_json::to_value(&command)?);
+        let position = existing
+            .iter()
+            .position(|(_, old)| old.get("name") == new.get("name") && old.get("type") == new.get("type"));
+
+        match position.map(|i| existing.remove(i)) {
+            None => {
+                target.create(ctx, command).await?;
+                changes.push(CommandChange::Created(display_name(&new)));
+            }
+            Some((id, old)) if old != new => {
+                target.edit(ctx, id, command).await?;
+                changes.push(CommandChange::Edited(display_name(&new), describe_changes(&old, &new)));
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (id, old) in existing {
+        target.delete(ctx, id).await?;
+        changes.push(CommandChange::Deleted(display_name(&old)));
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    /// Normalizes `command` the way `sync_commands` does with the desired commands.
+    fn desired(command: CreateCommand) -> Map<String, Value> {
+        normalize(serde_json::to_value(command).unwrap())
+    }
+
+    /// Normalizes a command payload the way `sync_commands` does with the fetched commands.
+    fn fetched(payload: Value) -> Map<String, Value> {
+        let command: Command = serde_json::from_value(payload).unwrap();
+        normalize(serde_json::to_value(command).unwrap())
+    }
+
+    fn number(name: &str, description: &str) -> CreateCommandOption {
+        CreateCommandOption::new(CommandOptionType::Number, name, description)
+    }
+
+    fn sum_command() -> CreateCommand {
+        CreateCommand::new("sum")
+            .description("Adds two numbers")
+            .name_localized("fr", "somme")
+            .description_localized("fr", "Additionne deux nombres")
+            .add_option(number("a", "First number").required(true).min_number_value(0.0).max_number_value(100.0))
+            .add_option(number("b", "Second number").description_localized("fr", "Deuxième nombre"))
+    }
+
+    fn option_payload(name: &str, description: &str) -> Value {
+        json!({
+            "type": 10,
+            "name": name,
+            "name_localizations": null,
+            "description": description,
+            "description_localizations": null,
+        })
+    }
+
+    /// `sum_command` as Discord returns it.
+    fn sum_payload() -> Value {
+        let mut a = option_payload("a", "First number");
+        a["required"] = json!(true);
+        a["min_value"] = json!(0);
+        a["max_value"] = json!(100);
+        let mut b = option_payload("b", "Second number");
+        b["required"] = json!(false);
+        b["description_localizations"] = json!({ "fr": "Deuxième nombre" });
+        json!({
+            "id": "1",
+            "application_id": "2",
+            "version": "3",
+            "type": 1,
+            "name": "sum",
+            "name_localizations": { "fr": "somme" },
+            "description": "Adds two numbers",
+            "description_localizations": { "fr": "Additionne deux nombres" },
+            "guild_id": null,
+            "default_member_permissions": null,
+            "dm_permission": true,
+            "nsfw": false,
+            "contexts": null,
+            "integration_types": [0],
+            "options": [a, b],
+        })
+    }
+
+    #[test]
+    fn unchanged_commands_compare_equal() {
+        let (old, new) = (fetched(sum_payload()), desired(sum_command()));
+        assert_eq!(old["name_localizations"], json!({ "fr": "somme" }));
+        assert_eq!(old["options"][0]["max_value"], json!(100.0));
+        assert_eq!(old, new);
+        assert!(describe_changes(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn context_menus_compare_equal() {
+        let payload = json!({
+            "id": "1",
+            "application_id": "2",
+            "version": "3",
+            "type": 2,
+            "name": "User info",
+            "name_localizations": null,
+            "description": "",
+            "description_localizations": null,
+            "default_member_permissions": null,
+            "dm_permission": true,
+            "nsfw": false,
+        });
+        assert_eq!(fetched(payload), desired(CreateCommand::new("User info").kind(CommandType::User)));
+    }
+
+    #[test]
+    fn disabling_dms_is_a_change() {
+        let old = fetched(sum_payload());
+        let new = desired(sum_command().dm_permission(false));
+        assert_eq!(describe_changes(&old, &new), ["dm_permission"]);
+    }
+
+    #[test]
+    fn description_changes_show_both_texts() {
+        let old = fetched(sum_payload());
+        let new = desired(sum_command().description("Adds numbers"));
+        assert_eq!(describe_changes(&old, &new), [r#"description "Adds two numbers" -> "Adds numbers""#]);
+    }
+
+    #[test]
+    fn added_options_are_listed() {
+        let old = fetched(sum_payload());
+        let new = desired(sum_command().add_option(number("c", "Third number")));
+        assert_eq!(describe_changes(&old, &new), ["options +c"]);
+    }
+
+    #[test]
+    fn removed_options_are_listed() {
+        let mut payload = sum_payload();
+        payload["options"].as_array_mut().unwrap().push(option_payload("c", "Third number"));
+        let (old, new) = (fetched(payload), desired(sum_command()));
+        assert_eq!(describe_changes(&old, &new), ["options -c"]);
+    }
+
+    #[test]
+    fn changed_options_are_listed() {
+        let mut payload = sum_payload();
+        payload["options"][0]["max_value"] = json!(50);
+        let (old, new) = (fetched(payload), desired(sum_command()));
+        assert_eq!(describe_changes(&old, &new), ["options ~a"]);
+    }
+
+    #[test]
+    fn reordered_options_are_listed() {
+        let mut payload = sum_payload();
+        payload["options"].as_array_mut().unwrap().reverse();
+        let (old, new) = (fetched(payload), desired(sum_command()));
+        assert_eq!(describe_changes(&old, &new), ["options order"]);
+    }
+
+    #[test]
+    fn changes_are_displayed_with_a_sign() {
+        assert_eq!(CommandChange::Created("/sum".into()).to_string(), "+ /sum");
+        assert_eq!(CommandChange::Edited("/sum".into(), vec!["options +c".into(), "nsfw".into()]).to_string(), "~ /sum (options +c, nsfw)");
+        assert_eq!(CommandChange::Deleted("User info".into()).to_string(), "- User info");
+    }
+}
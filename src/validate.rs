@@ -0,0 +1,373 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use serde_json::Value;
+use serenity::all::*;
+
+/// The maximum number of options, subcommands or choices at any level.
+const MAX_OPTIONS: usize = 25;
+/// The maximum length of a name.
+const MAX_NAME_LENGTH: usize = 32;
+/// The maximum length of a description, a choice name or a string choice value.
+const MAX_DESCRIPTION_LENGTH: usize = 100;
+/// The maximum combined length of the names, descriptions and choices of a command.
+const MAX_TOTAL_LENGTH: usize = 4000;
+/// The maximum value of `min_length` and `max_length`.
+const MAX_STRING_LENGTH: u64 = 6000;
+
+// Discord's option type values
+const SUB_COMMAND: u64 = 1;
+const SUB_COMMAND_GROUP: u64 = 2;
+
+/// A way in which a command breaks Discord's limits.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    /// Where the problem is, e.g. `/math convert celsius value` or `User info`.
+    pub location: String,
+    /// What is wrong.
+    pub problem: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`: {}", self.location, self.problem)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks a command against Discord's limits and returns every problem found.
+///
+/// This covers names (lowercase, 1-32 characters), descriptions (1-100 characters),
+/// the number of options and choices (25), how options and subcommands nest,
+/// and the 4000-character total of a command.
+pub fn validate_command(command: &CreateCommand) -> Vec<ValidationError> {
+    let json = serde_json::to_value(command).unwrap_or_default();
+    let name = str_field(&json, "name");
+    let is_slash = json.get("type").and_then(Value::as_u64).is_none_or(|kind| kind == u64::from(u8::from(CommandType::ChatInput)));
+
+    let mut validator = Validator { errors: Vec::new() };
+    if is_slash {
+        let location = format!("/{name}");
+        validator.check_slash_name(&location, &json);
+        validator.check_description(&location, &json);
+        let options = array_field(&json, "options");
+        validator.check_options(&location, options, Parent::Command);
+
+        let total = text_length(&json);
+        if total > MAX_TOTAL_LENGTH {
+            validator.error(&location, format!("names, descriptions and choices add up to {total} characters (limit {MAX_TOTAL_LENGTH})"));
+        }
+    } else {
+        validator.check_menu_name(name, &json);
+        if !str_field(&json, "description").is_empty() {
+            validator.error(name, "context-menu commands can't have a description");
+        }
+        if !array_field(&json, "options").is_empty() {
+            validator.error(name, "context-menu commands can't have options");
+        }
+    }
+    validator.errors
+}
+
+/// What contains a list of options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Parent {
+    Command,
+    Group,
+    Subcommand,
+}
+
+struct Validator {
+    errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    fn error(&mut self, location: &str, problem: impl Into<String>) {
+        self.errors.push(ValidationError { location: location.to_string(), problem: problem.into() });
+    }
+
+    /// Checks the name (and its translations) of a slash command or option.
+    fn check_slash_name(&mut self, location: &str, json: &Value) {
+        for (locale, name) in localized(json, "name") {
+            if let Some(reason) = slash_name_error(name) {
+                self.error(location, format!("name{locale} `{name}` is invalid: {reason}"));
+            }
+        }
+    }
+
+    /// Checks the name (and its translations) of a context-menu command.
+    fn check_menu_name(&mut self, location: &str, json: &Value) {
+        for (locale, name) in localized(json, "name") {
+            let length = name.chars().count();
+            if !(1..=MAX_NAME_LENGTH).contains(&length) {
+                self.error(location, format!("name{locale} is {length} characters long (1 to {MAX_NAME_LENGTH} allowed)"));
+            }
+        }
+    }
+
+    /// Checks the description (and its translations) of a slash command or option.
+    fn check_description(&mut self, location: &str, json: &Value) {
+        for (locale, description) in localized(json, "description") {
+            let length = description.chars().count();
+            if !(1..=MAX_DESCRIPTION_LENGTH).contains(&length) {
+                self.error(location, format!("description{locale} is {length} characters long (1 to {MAX_DESCRIPTION_LENGTH} allowed)"));
+            }
+        }
+    }
+
+    /// Checks a list of options and how it fits in its parent, then every option in it.
+    fn check_options(&mut self, location: &str, options: &[Value], parent: Parent) {
+        if options.len() > MAX_OPTIONS {
+            self.error(location, format!("has {} options (limit {MAX_OPTIONS})", options.len()));
+        }
+
+        let is_subcommand = |option: &Value| {
+            matches!(option.get("type").and_then(Value::as_u64), Some(SUB_COMMAND | SUB_COMMAND_GROUP))
+        };
+        let subcommands = options.iter().filter(|option| is_subcommand(option)).count();
+        match parent {
+            Parent::Command if subcommands > 0 && subcommands < options.len() => {
+                self.error(location, "mixes subcommands with regular options");
+            }
+            Parent::Group if subcommands < options.len() => {
+                self.error(location, "subcommand groups may only contain subcommands");
+            }
+            Parent::Subcommand if subcommands > 0 => {
+                self.error(location, "subcommands can't contain subcommands or groups");
+            }
+            _ => {}
+        }
+
+        let mut names = HashSet::new();
+        let mut seen_optional = false;
+        for option in options {
+            let name = str_field(option, "name");
+            if !names.insert(name) {
+                self.error(location, format!("has more than one option named `{name}`"));
+            }
+
+            let required = option.get("required").and_then(Value::as_bool).unwrap_or(false);
+            if required && seen_optional {
+                self.error(location, format!("required option `{name}` comes after an optional one"));
+            }
+            seen_optional |= !required && !is_subcommand(option);
+
+            self.check_option(&format!("{location} {name}"), option);
+        }
+    }
+
+    /// Checks a single option, subcommand or group.
+    fn check_option(&mut self, location: &str, option: &Value) {
+        self.check_slash_name(location, option);
+        self.check_description(location, option);
+
+        match option.get("type").and_then(Value::as_u64) {
+            Some(SUB_COMMAND) => self.check_options(location, array_field(option, "options"), Parent::Subcommand),
+            Some(SUB_COMMAND_GROUP) => self.check_options(location, array_field(option, "options"), Parent::Group),
+            _ => {}
+        }
+
+        let choices = array_field(option, "choices");
+        if choices.len() > MAX_OPTIONS {
+            self.error(location, format!("has {} choices (limit {MAX_OPTIONS})", choices.len()));
+        }
+        if !choices.is_empty() && option.get("autocomplete").and_then(Value::as_bool) == Some(true) {
+            self.error(location, "can't have both choices and autocomplete");
+        }
+        for choice in choices {
+            let name = str_field(choice, "name");
+            let length = name.chars().count();
+            if !(1..=MAX_DESCRIPTION_LENGTH).contains(&length) {
+                self.error(location, format!("choice `{name}` has a name of {length} characters (1 to {MAX_DESCRIPTION_LENGTH} allowed)"));
+            }
+            if let Some(value) = choice.get("value").and_then(Value::as_str)
+                && value.chars().count() > MAX_DESCRIPTION_LENGTH
+            {
+                self.error(location, format!("choice `{name}` has a value longer than {MAX_DESCRIPTION_LENGTH} characters"));
+            }
+        }
+
+        let min_length = option.get("min_length").and_then(Value::as_u64);
+        let max_length = option.get("max_length").and_then(Value::as_u64);
+        if min_length.is_some_and(|min| min > MAX_STRING_LENGTH) {
+            self.error(location, format!("min_length must be at most {MAX_STRING_LENGTH}"));
+        }
+        if max_length.is_some_and(|max| max == 0 || max > MAX_STRING_LENGTH) {
+            self.error(location, format!("max_length must be between 1 and {MAX_STRING_LENGTH}"));
+        }
+        if let (Some(min), Some(max)) = (min_length, max_length)
+            && min > max
+        {
+            self.error(location, "min_length is greater than max_length");
+        }
+
+        let min_value = option.get("min_value").and_then(Value::as_f64);
+        let max_value = option.get("max_value").and_then(Value::as_f64);
+        if let (Some(min), Some(max)) = (min_value, max_value)
+            && min > max
+        {
+            self.error(location, "min_value is greater than max_value");
+        }
+    }
+}
+
+/// Why Discord would reject `name` as a slash command or option name, if it would.
+fn slash_name_error(name: &str) -> Option<&'static str> {
+    let length = name.chars().count();
+    if !(1..=MAX_NAME_LENGTH).contains(&length) {
+        Some("it must be 1 to 32 characters long")
+    } else if name.chars().any(char::is_uppercase) {
+        Some("it must be lowercase")
+    } else if !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        Some("it may only contain letters, digits, `-` and `_`")
+    } else {
+        None
+    }
+}
+
+/// The value of `key` and of its translations in `{key}_localizations`, paired with
+/// a suffix naming the locale (empty for the default value).
+fn localized<'a>(json: &'a Value, key: &str) -> Vec<(String, &'a str)> {
+    let mut values = vec![(String::new(), str_field(json, key))];
+    if let Some(localizations) = json.get(format!("{key}_localizations")).and_then(Value::as_object) {
+        values.extend(
+            localizations
+                .iter()
+                .filter_map(|(locale, value)| Some((format!(" ({locale})"), value.as_str()?))),
+        );
+    }
+    values
+}
+
+/// The combined length of the names, descriptions and choices of a command, as counted by Discord.
+fn text_length(json: &Value) -> usize {
+    let own: usize = ["name", "description", "value"]
+        .iter()
+        .map(|key| str_field(json, key).chars().count())
+        .sum();
+    let nested: usize = ["options", "choices"]
+        .iter()
+        .flat_map(|key| array_field(json, key))
+        .map(text_length)
+        .sum();
+    own + nested
+}
+
+fn str_field<'a>(json: &'a Value, key: &str) -> &'a str {
+    json.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+fn array_field<'a>(json: &'a Value, key: &str) -> &'a [Value] {
+    json.get(key).and_then(Value::as_array).map_or(&[], Vec::as_slice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(kind: CommandOptionType, name: &str) -> CreateCommandOption {
+        CreateCommandOption::new(kind, name, "An option")
+    }
+
+    fn problems(command: CreateCommand) -> Vec<String> {
+        validate_command(&command).iter().map(ToString::to_string).collect()
+    }
+
+    fn assert_problem(command: CreateCommand, expected: &str) {
+        let problems = problems(command);
+        assert!(problems.iter().any(|problem| problem == expected), "{expected:?} not in {problems:?}");
+    }
+
+    #[test]
+    fn valid_commands_pass() {
+        let command = CreateCommand::new("remind")
+            .description("Sets a reminder")
+            .add_option(option(CommandOptionType::String, "text").required(true).max_length(200))
+            .add_option(option(CommandOptionType::Integer, "unit").add_int_choice("Minutes", 60).add_int_choice("Hours", 3600));
+        assert_eq!(problems(command), Vec::<String>::new());
+
+        let menu = CreateCommand::new("User Info").kind(CommandType::User);
+        assert_eq!(problems(menu), Vec::<String>::new());
+    }
+
+    #[test]
+    fn names_and_descriptions_are_checked() {
+        assert_problem(CreateCommand::new("Ping").description("Pong"), "`/Ping`: name `Ping` is invalid: it must be lowercase");
+        assert_problem(CreateCommand::new("ping pong").description("Pong"), "`/ping pong`: name `ping pong` is invalid: it may only contain letters, digits, `-` and `_`");
+        assert_problem(CreateCommand::new("a".repeat(33)).description("Long"), &format!("`/{}`: name `{}` is invalid: it must be 1 to 32 characters long", "a".repeat(33), "a".repeat(33)));
+        assert_problem(CreateCommand::new("ping").description("x".repeat(101)), "`/ping`: description is 101 characters long (1 to 100 allowed)");
+        assert_problem(CreateCommand::new("ping"), "`/ping`: description is 0 characters long (1 to 100 allowed)");
+    }
+
+    #[test]
+    fn option_counts_and_order_are_checked() {
+        let crowded = (0..26).fold(CreateCommand::new("crowded").description("Too many"), |command, i| {
+            command.add_option(option(CommandOptionType::String, &format!("option{i}")))
+        });
+        assert_problem(crowded, "`/crowded`: has 26 options (limit 25)");
+
+        let unordered = CreateCommand::new("sum")
+            .description("Adds")
+            .add_option(option(CommandOptionType::Integer, "a"))
+            .add_option(option(CommandOptionType::Integer, "b").required(true));
+        assert_problem(unordered, "`/sum`: required option `b` comes after an optional one");
+
+        let duplicated = CreateCommand::new("sum")
+            .description("Adds")
+            .add_option(option(CommandOptionType::Integer, "a"))
+            .add_option(option(CommandOptionType::Integer, "a"));
+        assert_problem(duplicated, "`/sum`: has more than one option named `a`");
+    }
+
+    #[test]
+    fn nesting_limits_are_checked() {
+        let nested = CreateCommand::new("math")
+            .description("Maths")
+            .add_option(option(CommandOptionType::SubCommand, "outer").add_sub_option(option(CommandOptionType::SubCommand, "inner")));
+        assert_problem(nested, "`/math outer`: subcommands can't contain subcommands or groups");
+
+        let loose_group = CreateCommand::new("math")
+            .description("Maths")
+            .add_option(option(CommandOptionType::SubCommandGroup, "convert").add_sub_option(option(CommandOptionType::Number, "value")));
+        assert_problem(loose_group, "`/math convert`: subcommand groups may only contain subcommands");
+
+        let mixed = CreateCommand::new("math")
+            .description("Maths")
+            .add_option(option(CommandOptionType::SubCommand, "add"))
+            .add_option(option(CommandOptionType::Number, "value"));
+        assert_problem(mixed, "`/math`: mixes subcommands with regular options");
+    }
+
+    #[test]
+    fn option_settings_are_checked() {
+        let both = CreateCommand::new("pick")
+            .description("Picks")
+            .add_option(option(CommandOptionType::String, "item").add_string_choice("One", "one").set_autocomplete(true));
+        assert_problem(both, "`/pick item`: can't have both choices and autocomplete");
+
+        let lengths = CreateCommand::new("say")
+            .description("Says")
+            .add_option(option(CommandOptionType::String, "text").min_length(10).max_length(5));
+        assert_problem(lengths, "`/say text`: min_length is greater than max_length");
+    }
+
+    #[test]
+    fn total_length_is_checked() {
+        let long = (0..25).fold(CreateCommand::new("long").description("x".repeat(100)), |command, i| {
+            command.add_option(option(CommandOptionType::String, &format!("option{i}")).description("x".repeat(100)).add_string_choice("y".repeat(100), "z"))
+        });
+        let problems = problems(long);
+        assert!(problems.iter().any(|problem| problem.starts_with("`/long`: names, descriptions and choices add up to")), "{problems:?}");
+    }
+
+    #[test]
+    fn context_menus_have_no_description_or_options() {
+        let menu = CreateCommand::new("Report")
+            .kind(CommandType::Message)
+            .description("Reports a message")
+            .add_option(option(CommandOptionType::String, "reason"));
+        assert_problem(menu.clone(), "`Report`: context-menu commands can't have a description");
+        assert_problem(menu, "`Report`: context-menu commands can't have options");
+    }
+}